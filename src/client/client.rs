@@ -1,5 +1,5 @@
 use crate::data::data_layer::Data;
-use crate::runtime::{executor::Executor, reciever::TcpReceiver, sender::TcpSender};
+use crate::runtime::{executor::Executor, reciever::TcpReceiver, sender::TcpSender, spawn_blocking};
 use std::{
     io,
     net::TcpStream,
@@ -7,18 +7,23 @@ use std::{
     time::Instant,
 };
 
-async fn send_data(field1: u32, field2: u16, field3: String) -> io::Result<String> {
-    let stream = Arc::new(Mutex::new(TcpStream::connect("127.0.0.1:7878")?));
-    let message = Data {
-        field1,
-        field2,
-        field3,
-    };
-    TcpSender {
+// Encode buffers handed back after each send, so that later messages reuse them instead
+// of allocating their own.
+type BufferPool = Arc<Mutex<Vec<Vec<u8>>>>;
+
+async fn send_data(message: Data, pool: BufferPool) -> io::Result<String> {
+    let stream = spawn_blocking(|| TcpStream::connect("127.0.0.1:7878")).await??;
+    let stream = Arc::new(Mutex::new(stream));
+    let mut buffer = pool.lock().unwrap().pop().unwrap_or_default();
+    buffer.clear();
+    message.serialize_into(&mut buffer)?;
+    let mut sender = TcpSender {
         stream: stream.clone(),
-        buffer: message.serialize()?,
-    }
-    .await?;
+        buffer,
+    };
+    let sent = (&mut sender).await;
+    pool.lock().unwrap().push(sender.buffer);
+    sent?;
     let receiver = TcpReceiver {
         stream: stream.clone(),
         buffer: Vec::new(),
@@ -31,8 +36,14 @@ fn main() -> io::Result<()> {
     let mut executor = Executor::new();
     let mut handles = Vec::new();
     let start = Instant::now();
+    let pool = BufferPool::default();
     for i in 0..4000 {
-        let handle = executor.spawn(send_data(i, i as u16, format!("Hello, server! {}", i)));
+        let message = Data {
+            field1: i,
+            field2: i as u16,
+            field3: format!("Hello, server! {}", i),
+        };
+        let handle = executor.spawn(send_data(message, pool.clone()));
         handles.push(handle);
     }
     std::thread::spawn(move || {
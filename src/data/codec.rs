@@ -0,0 +1,578 @@
+use crate::data::error::DecodeError;
+use crate::data::limits::{DecodeLimits, Limit, LimitExceeded};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    io::{self, Write},
+};
+
+/// A value with a defined wire encoding.
+///
+/// Integers and floats are big-endian and fixed width, `usize`/`isize` travel as 64 bits,
+/// `bool` and the `Option` tag are one byte, and strings, vectors and maps are prefixed
+/// with their length as a `u32`. Arrays and tuples are their elements back to back.
+pub trait Encode {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+    /// Exactly how many bytes `encode` writes.
+    fn encoded_len(&self) -> usize;
+
+    fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        self.encode(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Encodes into the front of `buf` and returns how many bytes that took. Fails with
+    /// `WriteZero`, before writing anything, if `buf` is too short.
+    fn encode_to_slice(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.encoded_len();
+        let have = buf.len();
+        let mut slot = buf.get_mut(..len).ok_or_else(|| too_short(len, have))?;
+        self.encode(&mut slot)?;
+        Ok(len)
+    }
+}
+
+pub(crate) fn too_short(needed: usize, len: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::WriteZero,
+        format!("needed {} bytes but the buffer only has {}", needed, len),
+    )
+}
+
+/// A value that can be read back from its [`Encode`] form. The lifetime lets
+/// implementations borrow from the input.
+pub trait Decode<'de>: Sized {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError>;
+
+    /// Decodes a value that must take up all of `bytes`, under the default [`DecodeLimits`].
+    fn from_bytes(bytes: &'de [u8]) -> Result<Self, DecodeError> {
+        Self::from_bytes_with_limits(bytes, DecodeLimits::default())
+    }
+
+    fn from_bytes_with_limits(bytes: &'de [u8], limits: DecodeLimits) -> Result<Self, DecodeError> {
+        let mut decoder = Decoder::with_limits(bytes, limits);
+        let value = Self::decode(&mut decoder)?;
+        decoder.finish()?;
+        Ok(value)
+    }
+}
+
+/// Reads encoded values from a byte slice, within a set of [`DecodeLimits`].
+pub struct Decoder<'de> {
+    bytes: &'de [u8],
+    position: usize,
+    /// Offset of `bytes` in the original input, for decoders made by [`Decoder::split`].
+    base: usize,
+    limits: DecodeLimits,
+    depth: usize,
+    /// Set on decoders made by [`Decoder::split`]. Their bytes are already all there, so
+    /// running out means the value overruns its region rather than that input is missing.
+    bounded: bool,
+}
+
+impl<'de> Decoder<'de> {
+    pub fn new(bytes: &'de [u8]) -> Self {
+        Decoder::with_limits(bytes, DecodeLimits::default())
+    }
+
+    pub fn with_limits(bytes: &'de [u8], limits: DecodeLimits) -> Self {
+        Decoder {
+            bytes,
+            position: 0,
+            base: 0,
+            limits,
+            depth: 0,
+            bounded: false,
+        }
+    }
+
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+
+    /// Bytes consumed so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Where the next byte sits in the original input. Errors report offsets this way.
+    pub fn offset(&self) -> usize {
+        self.base + self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    pub fn decode<T: Decode<'de>>(&mut self) -> Result<T, DecodeError> {
+        T::decode(self)
+    }
+
+    /// Fails if any input is left over.
+    pub fn finish(&self) -> Result<(), DecodeError> {
+        if self.remaining() != 0 {
+            return Err(DecodeError::TrailingBytes {
+                count: self.remaining(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Takes the next `len` bytes without copying them.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'de [u8], DecodeError> {
+        let bytes = self.peek_bytes(len)?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    /// Like [`Decoder::read_bytes`], but leaves them to be read again.
+    pub fn peek_bytes(&self, len: usize) -> Result<&'de [u8], DecodeError> {
+        let end = self.offset().saturating_add(len);
+        if end > self.limits.max_total_bytes {
+            return Err(self.limit_exceeded(Limit::TotalBytes, self.limits.max_total_bytes, end));
+        }
+        if len > self.remaining() && self.bounded {
+            return Err(DecodeError::invalid(
+                self.offset(),
+                format!(
+                    "needed {} bytes but the enclosing value has only {} left",
+                    len,
+                    self.remaining()
+                ),
+            ));
+        }
+        if len > self.remaining() {
+            return Err(DecodeError::Truncated {
+                offset: self.offset(),
+                needed: len,
+                remaining: self.remaining(),
+            });
+        }
+        Ok(&self.bytes[self.position..self.position + len])
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    /// Reads a `u32` length prefix.
+    pub fn read_len(&mut self) -> Result<usize, DecodeError> {
+        Ok(u32::from_be_bytes(self.read_array()?) as usize)
+    }
+
+    /// Reads a string's length prefix and checks it against the string limit.
+    pub fn read_string_len(&mut self) -> Result<usize, DecodeError> {
+        let len = self.read_len()?;
+        self.check_string_len(len, self.offset() - LEN_PREFIX)?;
+        Ok(len)
+    }
+
+    /// Reads a vector's or map's length prefix and checks it against the collection limit.
+    pub fn read_collection_len(&mut self) -> Result<usize, DecodeError> {
+        let len = self.read_len()?;
+        self.check_collection_len(len, self.offset() - LEN_PREFIX)?;
+        Ok(len)
+    }
+
+    /// Takes a UTF-8 string of `len` bytes without copying it.
+    pub fn read_str(&mut self, len: usize) -> Result<&'de str, DecodeError> {
+        let offset = self.offset();
+        std::str::from_utf8(self.read_bytes(len)?)
+            .map_err(|_| DecodeError::InvalidUtf8 { field: None, offset })
+    }
+
+    /// For string encodings with their own kind of prefix, which starts at `offset`.
+    pub fn check_string_len(&self, len: usize, offset: usize) -> Result<(), DecodeError> {
+        if len > self.limits.max_string_len {
+            return Err(LimitExceeded {
+                limit: Limit::StringLength,
+                max: self.limits.max_string_len,
+                actual: len,
+                offset,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// For collection encodings with their own kind of prefix, which starts at `offset`.
+    pub fn check_collection_len(&self, len: usize, offset: usize) -> Result<(), DecodeError> {
+        if len > self.limits.max_collection_len {
+            return Err(LimitExceeded {
+                limit: Limit::CollectionLength,
+                max: self.limits.max_collection_len,
+                actual: len,
+                offset,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Runs `f` one nesting level deeper. Containers and composite types decode their
+    /// contents through this so that deeply nested input is cut off at the depth limit.
+    pub fn nested<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, DecodeError>,
+    ) -> Result<T, DecodeError> {
+        if self.depth >= self.limits.max_depth {
+            return Err(self.limit_exceeded(Limit::Depth, self.limits.max_depth, self.depth + 1));
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// A decoder over the next `len` bytes that shares this one's limits and depth. Reading
+    /// past its end is [`DecodeError::Invalid`], not [`DecodeError::Truncated`].
+    pub fn split(&mut self, len: usize) -> Result<Decoder<'de>, DecodeError> {
+        let base = self.offset();
+        Ok(Decoder {
+            bytes: self.read_bytes(len)?,
+            position: 0,
+            base,
+            limits: self.limits,
+            depth: self.depth,
+            bounded: true,
+        })
+    }
+
+    fn limit_exceeded(&self, limit: Limit, max: usize, actual: usize) -> DecodeError {
+        LimitExceeded {
+            limit,
+            max,
+            actual,
+            offset: self.offset(),
+        }
+        .into()
+    }
+}
+
+/// Writes `len` as a `u32` length prefix.
+pub fn encode_len<W: Write>(len: usize, writer: &mut W) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("length {} does not fit the u32 prefix", len),
+        )
+    })?;
+    writer.write_all(&len.to_be_bytes())
+}
+
+const LEN_PREFIX: usize = 4;
+
+macro_rules! number_impls {
+    ($($t:ty)*) => {$(
+        impl Encode for $t {
+            fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                writer.write_all(&self.to_be_bytes())
+            }
+
+            fn encoded_len(&self) -> usize {
+                size_of::<$t>()
+            }
+        }
+
+        impl<'de> Decode<'de> for $t {
+            fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+                Ok(<$t>::from_be_bytes(decoder.read_array()?))
+            }
+        }
+    )*};
+}
+
+number_impls!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128 f32 f64);
+
+macro_rules! size_impls {
+    ($($t:ty => $wire:ty),*) => {$(
+        impl Encode for $t {
+            fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                (*self as $wire).encode(writer)
+            }
+
+            fn encoded_len(&self) -> usize {
+                size_of::<$wire>()
+            }
+        }
+
+        impl<'de> Decode<'de> for $t {
+            fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+                let offset = decoder.offset();
+                let value = <$wire>::decode(decoder)?;
+                <$t>::try_from(value).map_err(|_| {
+                    DecodeError::invalid(
+                        offset,
+                        format!("{} does not fit in {}", value, stringify!($t)),
+                    )
+                })
+            }
+        }
+    )*};
+}
+
+size_impls!(usize => u64, isize => i64);
+
+impl Encode for bool {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (*self as u8).encode(writer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        1
+    }
+}
+
+impl<'de> Decode<'de> for bool {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        let offset = decoder.offset();
+        match u8::decode(decoder)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(DecodeError::invalid(offset, format!("invalid bool byte {}", byte))),
+        }
+    }
+}
+
+impl Encode for str {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        encode_len(self.len(), writer)?;
+        writer.write_all(self.as_bytes())
+    }
+
+    fn encoded_len(&self) -> usize {
+        LEN_PREFIX + self.len()
+    }
+}
+
+impl Encode for String {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.as_str().encode(writer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.as_str().encoded_len()
+    }
+}
+
+impl<'de> Decode<'de> for &'de str {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        let len = decoder.read_string_len()?;
+        decoder.read_str(len)
+    }
+}
+
+impl<'de> Decode<'de> for String {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        <&str>::decode(decoder).map(String::from)
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (**self).encode(writer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        (**self).encoded_len()
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        encode_len(self.len(), writer)?;
+        self.iter().try_for_each(|item| item.encode(writer))
+    }
+
+    fn encoded_len(&self) -> usize {
+        LEN_PREFIX + self.iter().map(Encode::encoded_len).sum::<usize>()
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.as_slice().encode(writer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.as_slice().encoded_len()
+    }
+}
+
+/// How many `T`s to reserve for a collection claiming `len` of them. The length comes from
+/// the input and an element may decode from fewer bytes than it takes in memory, so the
+/// reservation is kept to about the size of the input left rather than trusted.
+pub(crate) fn preallocate<T>(len: usize, remaining: usize) -> usize {
+    len.min(remaining / size_of::<T>().max(1))
+}
+
+impl<'de, T: Decode<'de>> Decode<'de> for Vec<T> {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        let len = decoder.read_collection_len()?;
+        decoder.nested(|decoder| {
+            let mut items = Vec::with_capacity(preallocate::<T>(len, decoder.remaining()));
+            for _ in 0..len {
+                items.push(T::decode(decoder)?);
+            }
+            Ok(items)
+        })
+    }
+}
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.iter().try_for_each(|item| item.encode(writer))
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.iter().map(Encode::encoded_len).sum()
+    }
+}
+
+impl<'de, T: Decode<'de>, const N: usize> Decode<'de> for [T; N] {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        let mut items = Vec::with_capacity(N);
+        for _ in 0..N {
+            items.push(T::decode(decoder)?);
+        }
+        Ok(items
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("decoded exactly N items")))
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Some(value) => {
+                true.encode(writer)?;
+                value.encode(writer)
+            }
+            None => false.encode(writer),
+        }
+    }
+
+    fn encoded_len(&self) -> usize {
+        1 + self.as_ref().map_or(0, Encode::encoded_len)
+    }
+}
+
+impl<'de, T: Decode<'de>> Decode<'de> for Option<T> {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        match bool::decode(decoder)? {
+            true => decoder.nested(T::decode).map(Some),
+            false => Ok(None),
+        }
+    }
+}
+
+fn encode_map<'a, K, V, W>(
+    len: usize,
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+    writer: &mut W,
+) -> io::Result<()>
+where
+    K: Encode + 'a,
+    V: Encode + 'a,
+    W: Write,
+{
+    encode_len(len, writer)?;
+    for (key, value) in entries {
+        key.encode(writer)?;
+        value.encode(writer)?;
+    }
+    Ok(())
+}
+
+fn map_encoded_len<'a, K: Encode + 'a, V: Encode + 'a>(
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+) -> usize {
+    LEN_PREFIX
+        + entries
+            .map(|(key, value)| key.encoded_len() + value.encoded_len())
+            .sum::<usize>()
+}
+
+/// Entries are written in iteration order, so equal maps may encode differently.
+impl<K: Encode, V: Encode, S> Encode for HashMap<K, V, S> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        encode_map(self.len(), self.iter(), writer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        map_encoded_len(self.iter())
+    }
+}
+
+impl<'de, K: Decode<'de> + Eq + Hash, V: Decode<'de>> Decode<'de> for HashMap<K, V> {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        let len = decoder.read_collection_len()?;
+        decoder.nested(|decoder| {
+            let mut map = HashMap::with_capacity(preallocate::<(K, V)>(len, decoder.remaining()));
+            for _ in 0..len {
+                map.insert(K::decode(decoder)?, V::decode(decoder)?);
+            }
+            Ok(map)
+        })
+    }
+}
+
+impl<K: Encode, V: Encode> Encode for BTreeMap<K, V> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        encode_map(self.len(), self.iter(), writer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        map_encoded_len(self.iter())
+    }
+}
+
+impl<'de, K: Decode<'de> + Ord, V: Decode<'de>> Decode<'de> for BTreeMap<K, V> {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        let len = decoder.read_collection_len()?;
+        decoder.nested(|decoder| {
+            let mut map = BTreeMap::new();
+            for _ in 0..len {
+                map.insert(K::decode(decoder)?, V::decode(decoder)?);
+            }
+            Ok(map)
+        })
+    }
+}
+
+macro_rules! tuple_impls {
+    ($($name:ident)+) => {
+        #[allow(non_snake_case)]
+        impl<$($name: Encode),+> Encode for ($($name,)+) {
+            fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                let ($($name,)+) = self;
+                $($name.encode(writer)?;)+
+                Ok(())
+            }
+
+            fn encoded_len(&self) -> usize {
+                let ($($name,)+) = self;
+                0 $(+ $name.encoded_len())+
+            }
+        }
+
+        impl<'de, $($name: Decode<'de>),+> Decode<'de> for ($($name,)+) {
+            fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+                Ok(($($name::decode(decoder)?,)+))
+            }
+        }
+    };
+}
+
+tuple_impls!(A);
+tuple_impls!(A B);
+tuple_impls!(A B C);
+tuple_impls!(A B C D);
+tuple_impls!(A B C D E);
+tuple_impls!(A B C D E F);
+tuple_impls!(A B C D E F G);
+tuple_impls!(A B C D E F G H);
@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod tests {
+    use crate::data::codec::{Decode, Decoder, Encode, preallocate};
+    use crate::data::error::DecodeError;
+    use crate::data::limits::DecodeLimits;
+    use std::collections::{BTreeMap, HashMap};
+    use std::fmt::Debug;
+    use std::io::{self, ErrorKind, Write};
+
+    fn round_trip<T>(value: T)
+    where
+        T: Encode + for<'de> Decode<'de> + PartialEq + Debug,
+    {
+        let bytes = value.to_bytes().expect("encoding should succeed");
+        assert_eq!(bytes.len(), value.encoded_len(), "encoded_len of {:?}", value);
+        assert_eq!(T::from_bytes(&bytes).expect("decoding should succeed"), value);
+    }
+
+    #[test]
+    fn test_numbers_round_trip() {
+        round_trip(u8::MAX);
+        round_trip(u16::MAX);
+        round_trip(u32::MAX);
+        round_trip(u64::MAX);
+        round_trip(u128::MAX);
+        round_trip(i8::MIN);
+        round_trip(i16::MIN);
+        round_trip(i32::MIN);
+        round_trip(i64::MIN);
+        round_trip(i128::MIN);
+        round_trip(usize::MAX);
+        round_trip(isize::MIN);
+        round_trip(1.5f32);
+        round_trip(-2.25f64);
+        round_trip(true);
+        round_trip(false);
+    }
+
+    #[test]
+    fn test_numbers_are_big_endian() {
+        assert_eq!(0x0102_0304u32.to_bytes().unwrap(), [1, 2, 3, 4]);
+        assert_eq!((-2i16).to_bytes().unwrap(), [0xFF, 0xFE]);
+        assert_eq!(1usize.to_bytes().unwrap(), [0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(1.0f32.to_bytes().unwrap(), 1.0f32.to_be_bytes());
+    }
+
+    #[test]
+    fn test_containers_round_trip() {
+        round_trip(String::from("Hello, 世界! 🦀"));
+        round_trip(vec![1u16, 2, 3]);
+        round_trip(Vec::<String>::new());
+        round_trip(Some(String::from("present")));
+        round_trip(None::<u32>);
+        round_trip((1u8, String::from("two"), 3.0f64));
+        round_trip([7u32; 4]);
+        round_trip(vec![Some((1u8, false)), None]);
+        round_trip(HashMap::from([(String::from("a"), 1u32), (String::from("b"), 2)]));
+        round_trip(BTreeMap::from([(1u8, vec![String::from("x")]), (2, Vec::new())]));
+    }
+
+    #[test]
+    fn test_length_prefixes() {
+        assert_eq!(String::from("hi").to_bytes().unwrap(), [0, 0, 0, 2, b'h', b'i']);
+        assert_eq!(vec![1u8, 2].to_bytes().unwrap(), [0, 0, 0, 2, 1, 2]);
+        assert_eq!([1u8, 2].to_bytes().unwrap(), [1, 2]);
+        assert_eq!(Some(5u8).to_bytes().unwrap(), [1, 5]);
+        assert_eq!(None::<u8>.to_bytes().unwrap(), [0]);
+    }
+
+    #[test]
+    fn test_borrowed_str() {
+        let bytes = "borrowed".to_bytes().unwrap();
+        let decoded = <&str>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, "borrowed");
+        assert_eq!(decoded.as_ptr(), bytes[4..].as_ptr());
+    }
+
+    #[test]
+    fn test_invalid_input_is_rejected() {
+        let error = bool::from_bytes(&[2]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+        let error = String::from_bytes(&[0, 0, 0, 2, 0xFF, 0xFE]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+        let error = u32::from_bytes(&[1, 2]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+
+        // A huge length prefix must fail without trying to allocate for it, even when no
+        // limit catches it first.
+        let error = Vec::<u64>::from_bytes_with_limits(
+            &[0xFF, 0xFF, 0xFF, 0xFF, 1],
+            DecodeLimits::unlimited(),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+
+        let error = u8::from_bytes(&[1, 2]).unwrap_err();
+        assert_eq!(error.to_string(), "1 trailing bytes after value");
+    }
+
+    #[test]
+    fn test_preallocation_is_capped_by_input_bytes() {
+        // A claimed length is only trusted as far as the input could back it in memory.
+        assert_eq!(preallocate::<u8>(1_000_000, 64), 64);
+        assert_eq!(preallocate::<u64>(1_000_000, 64), 8);
+        assert_eq!(preallocate::<Vec<u8>>(1_000_000, 64), 64 / size_of::<Vec<u8>>());
+        assert_eq!(preallocate::<u64>(3, 64), 3);
+        assert_eq!(preallocate::<()>(5, 0), 0);
+    }
+
+    #[test]
+    fn test_decoder_reads_a_sequence() {
+        let mut bytes = Vec::new();
+        7u8.encode(&mut bytes).unwrap();
+        "next".encode(&mut bytes).unwrap();
+        let mut decoder = Decoder::new(&bytes);
+        assert_eq!(decoder.decode::<u8>().unwrap(), 7);
+        assert_eq!(decoder.position(), 1);
+        assert_eq!(decoder.decode::<String>().unwrap(), "next");
+        assert_eq!(decoder.remaining(), 0);
+    }
+
+    // A message type built from the provided impls, as an application would write one.
+    #[derive(Debug, PartialEq)]
+    struct Login {
+        user: String,
+        roles: Vec<String>,
+        session: Option<u64>,
+    }
+
+    impl Encode for Login {
+        fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+            self.user.encode(writer)?;
+            self.roles.encode(writer)?;
+            self.session.encode(writer)
+        }
+
+        fn encoded_len(&self) -> usize {
+            self.user.encoded_len() + self.roles.encoded_len() + self.session.encoded_len()
+        }
+    }
+
+    impl<'de> Decode<'de> for Login {
+        fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+            Ok(Login {
+                user: decoder.decode()?,
+                roles: decoder.decode()?,
+                session: decoder.decode()?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_custom_message_round_trip() {
+        round_trip(Login {
+            user: String::from("ferris"),
+            roles: vec![String::from("admin")],
+            session: Some(42),
+        });
+    }
+}
@@ -1,57 +1,234 @@
-use std::io::{self, Cursor, Read, Write};
+use crate::data::codec::{Decode, Decoder, Encode};
+use crate::data::error::DecodeError;
+use crate::data::limits::DecodeLimits;
+use std::io::{self, Cursor, Write};
 
+/// Leading bytes of every frame, followed by the version byte.
+pub const MAGIC: [u8; 2] = [0xDA, 0x7A];
+/// Wire format version written by [`Data::serialize`]. Version 1 is big-endian throughout.
+pub const VERSION: u8 = 1;
+/// Bytes taken by the magic and version.
+pub const HEADER_LEN: usize = MAGIC.len() + 1;
+
+/// A message. Its [`Encode`] and [`Decode`] forms are whole frames: the magic, the
+/// version, then the fields.
 #[derive(Debug)]
 pub struct Data {
     pub field1: u32,
     pub field2: u16,
     pub field3: String,
 }
+
+/// A [`Data`] that borrows `field3` from the bytes it was decoded from instead of copying
+/// it. Decoding one allocates nothing; [`DataRef::to_owned`] copies it out when it has to
+/// outlive the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DataRef<'a> {
+    pub field1: u32,
+    pub field2: u16,
+    pub field3: &'a str,
+}
+
+crate::wire_struct! {
+    // What follows the header in a frame.
+    struct Fields<'a> {
+        field1: u32,
+        field2: u16,
+        field3: &'a str,
+    }
+}
+
+impl Encode for Data {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        DataRef::from(self).encode(writer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        DataRef::from(self).encoded_len()
+    }
+}
+
+impl<'de> Decode<'de> for Data {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        DataRef::decode(decoder).map(|data| data.to_owned())
+    }
+}
+
+impl Encode for DataRef<'_> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[MAGIC[0], MAGIC[1], VERSION])?;
+        Fields::from(*self).encode(writer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        HEADER_LEN + Fields::from(*self).encoded_len()
+    }
+}
+
+impl<'de> Decode<'de> for DataRef<'de> {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        if decoder.read_array()? != MAGIC {
+            return Err(DecodeError::MissingHeader);
+        }
+        let version = decoder.decode::<u8>()?;
+        if version != VERSION {
+            return Err(DecodeError::BadVersion {
+                found: version,
+                expected: VERSION,
+            });
+        }
+        let Fields {
+            field1,
+            field2,
+            field3,
+        } = decoder.decode()?;
+        Ok(DataRef {
+            field1,
+            field2,
+            field3,
+        })
+    }
+}
+
+impl<'a> From<DataRef<'a>> for Fields<'a> {
+    fn from(data: DataRef<'a>) -> Self {
+        Fields {
+            field1: data.field1,
+            field2: data.field2,
+            field3: data.field3,
+        }
+    }
+}
+
 impl Data {
+    /// Encodes the frame into a new buffer. The same as [`Encode::to_bytes`].
     pub fn serialize(&self) -> io::Result<Vec<u8>> {
-        let mut bytes = Vec::new();
-        bytes.write(&self.field1.to_ne_bytes())?;
-        bytes.write(&self.field2.to_ne_bytes())?;
-        let field3_len = self.field3.len() as u32;
-        bytes.write(&field3_len.to_ne_bytes())?;
-        bytes.extend_from_slice(self.field3.as_bytes());
-        Ok(bytes)
+        self.to_bytes()
+    }
+
+    /// Writes the frame to `writer`, so that callers can reuse one buffer across messages.
+    /// [`Encode::encoded_len`] and [`Encode::encode_to_slice`] cover fixed-size buffers.
+    pub fn serialize_into<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.encode(writer)
+    }
+
+    /// Decodes a frame written by [`Data::serialize`], rejecting frames without the header
+    /// and frames of any other version.
+    pub fn deserialize(cursor: &mut Cursor<&[u8]>) -> Result<Data, DecodeError> {
+        Data::deserialize_with_limits(cursor, DecodeLimits::default())
     }
-    pub fn deserialize(cursor: &mut Cursor<&[u8]>) -> io::Result<Data> {
-        // Initialize buffers for the fields, using arrays of the appropriate size
-        let mut field1_bytes = [0u8; 4];
-        let mut field2_bytes = [0u8; 2];
 
-        // Read the first field (4 bytes) from the cursor into the buffer. Do the same for second field.
-        cursor.read_exact(&mut field1_bytes)?;
-        cursor.read_exact(&mut field2_bytes)?;
+    pub fn deserialize_with_limits(
+        cursor: &mut Cursor<&[u8]>,
+        limits: DecodeLimits,
+    ) -> Result<Data, DecodeError> {
+        with_decoder(cursor, limits, Data::decode_frame)
+    }
+
+    /// Like [`Data::deserialize`], but also accepts the headerless native-endian frames
+    /// written before the wire format was versioned. Meant for the migration period only: a
+    /// legacy frame whose first bytes happen to match the magic is decoded as a new one.
+    pub fn deserialize_with_legacy(cursor: &mut Cursor<&[u8]>) -> Result<Data, DecodeError> {
+        with_decoder(cursor, DecodeLimits::default(), Data::decode_frame_with_legacy)
+    }
+
+    /// Decodes a whole frame, header included, from a decoder. The same as
+    /// [`Decode::decode`], as a function to hand to
+    /// [`StreamDecoder::with_decode_fn`](crate::data::stream::StreamDecoder::with_decode_fn).
+    pub fn decode_frame(decoder: &mut Decoder<'_>) -> Result<Data, DecodeError> {
+        Data::decode(decoder)
+    }
+
+    /// [`Data::decode_frame`] with the legacy fallback of [`Data::deserialize_with_legacy`].
+    pub fn decode_frame_with_legacy(decoder: &mut Decoder<'_>) -> Result<Data, DecodeError> {
+        DataRef::decode_frame_with_legacy(decoder).map(|data| data.to_owned())
+    }
+}
 
-        // Convert the byte arrays into the appropriate data types (u32 and u16)
-        let field1 = u32::from_ne_bytes(field1_bytes);
-        let field2 = u16::from_ne_bytes(field2_bytes);
+impl<'a> DataRef<'a> {
+    /// Decodes a frame written by [`Data::serialize`], borrowing `field3` from the input.
+    pub fn deserialize(cursor: &mut Cursor<&'a [u8]>) -> Result<DataRef<'a>, DecodeError> {
+        DataRef::deserialize_with_limits(cursor, DecodeLimits::default())
+    }
 
-        // Initialize a buffer to read the length of the third field , which is 4 bytes long
-        let mut len_bytes = [0u8; 4];
+    pub fn deserialize_with_limits(
+        cursor: &mut Cursor<&'a [u8]>,
+        limits: DecodeLimits,
+    ) -> Result<DataRef<'a>, DecodeError> {
+        with_decoder(cursor, limits, DataRef::decode_frame)
+    }
 
-        // Read the length from the cursor into the buffer
-        cursor.read_exact(&mut len_bytes)?;
+    /// See [`Data::deserialize_with_legacy`].
+    pub fn deserialize_with_legacy(
+        cursor: &mut Cursor<&'a [u8]>,
+    ) -> Result<DataRef<'a>, DecodeError> {
+        with_decoder(cursor, DecodeLimits::default(), DataRef::decode_frame_with_legacy)
+    }
 
-        // Convert the length bytes into a usize
-        let len = u32::from_ne_bytes(len_bytes) as usize;
+    pub fn decode_frame(decoder: &mut Decoder<'a>) -> Result<DataRef<'a>, DecodeError> {
+        DataRef::decode(decoder)
+    }
 
-        // Initialize a buffer with the specified length to hold the third field's data
-        let mut field3_bytes = vec![0u8; len];
+    pub fn decode_frame_with_legacy(decoder: &mut Decoder<'a>) -> Result<DataRef<'a>, DecodeError> {
+        if decoder.peek_bytes(MAGIC.len())? == MAGIC {
+            return DataRef::decode_frame(decoder);
+        }
+        DataRef::decode_legacy(decoder)
+    }
 
-        // Read the third field's data from the cursor into the buffer
-        cursor.read_exact(&mut field3_bytes)?;
+    fn decode_legacy(decoder: &mut Decoder<'a>) -> Result<DataRef<'a>, DecodeError> {
+        // The fixed-width fields, in the sender's native byte order
+        let field1 = u32::from_ne_bytes(decoder.read_array()?);
+        let field2 = u16::from_ne_bytes(decoder.read_array()?);
 
-        // Convert the third field's bytes into a UTF-8 string, or return an error if this cannot be done.
-        let field3 = String::from_utf8(field3_bytes)
-            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8"))?;
+        // The string's length is checked against the limits before anything is read for it
+        let offset = decoder.offset();
+        let len = u32::from_ne_bytes(decoder.read_array()?) as usize;
+        decoder.check_string_len(len, offset)?;
+        let field3 = decoder.read_str(len).map_err(|err| err.in_field("field3"))?;
 
-        Ok(Data {
+        Ok(DataRef {
             field1,
             field2,
             field3,
         })
     }
+
+    /// Writes the same frame as [`Data::serialize_into`].
+    pub fn serialize_into<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.encode(writer)
+    }
+
+    /// Copies the borrowed string into an owned [`Data`].
+    pub fn to_owned(&self) -> Data {
+        Data {
+            field1: self.field1,
+            field2: self.field2,
+            field3: self.field3.to_owned(),
+        }
+    }
+}
+
+impl<'a> From<&'a Data> for DataRef<'a> {
+    fn from(data: &'a Data) -> Self {
+        DataRef {
+            field1: data.field1,
+            field2: data.field2,
+            field3: &data.field3,
+        }
+    }
+}
+
+/// Decodes from the cursor's position on and then moves the cursor past what was read.
+fn with_decoder<'a, T>(
+    cursor: &mut Cursor<&'a [u8]>,
+    limits: DecodeLimits,
+    f: impl FnOnce(&mut Decoder<'a>) -> Result<T, DecodeError>,
+) -> Result<T, DecodeError> {
+    let start = cursor.position() as usize;
+    let bytes = cursor.get_ref().get(start..).unwrap_or_default();
+    let mut decoder = Decoder::with_limits(bytes, limits);
+    let value = f(&mut decoder)?;
+    cursor.set_position((start + decoder.position()) as u64);
+    Ok(value)
 }
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use crate::data::data_layer::Data;
+    use crate::data::codec::{Decode, Encode};
+    use crate::data::data_layer::{Data, DataRef, HEADER_LEN, MAGIC, VERSION};
     use std::io::Cursor;
 
     #[test]
@@ -17,8 +18,8 @@ mod tests {
         assert!(!serialized.is_empty());
         
         // The serialized data should have:
-        // 4 bytes for field1 + 2 bytes for field2 + 4 bytes for string length + string bytes
-        let expected_len = 4 + 2 + 4 + "Hello, World!".len();
+        // 3 header bytes + 4 bytes for field1 + 2 bytes for field2 + 4 bytes for string length + string bytes
+        let expected_len = HEADER_LEN + 4 + 2 + 4 + "Hello, World!".len();
         assert_eq!(serialized.len(), expected_len);
     }
 
@@ -111,12 +112,14 @@ mod tests {
     fn test_data_deserialization_invalid_utf8() {
         let mut bytes = Vec::new();
         
-        // Write valid field1 and field2
-        bytes.extend_from_slice(&42u32.to_ne_bytes());
-        bytes.extend_from_slice(&1337u16.to_ne_bytes());
+        // Write the header and valid field1 and field2
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&42u32.to_be_bytes());
+        bytes.extend_from_slice(&1337u16.to_be_bytes());
         
         // Write length for string
-        bytes.extend_from_slice(&3u32.to_ne_bytes());
+        bytes.extend_from_slice(&3u32.to_be_bytes());
         
         // Write invalid UTF-8 bytes
         bytes.extend_from_slice(&[0xFF, 0xFE, 0xFD]);
@@ -143,4 +146,182 @@ mod tests {
         assert_eq!(deserialized.field2, 200);
         assert_eq!(deserialized.field3, "");
     }
+
+    fn legacy_frame(field1: u32, field2: u16, field3: &str) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&field1.to_ne_bytes());
+        bytes.extend_from_slice(&field2.to_ne_bytes());
+        bytes.extend_from_slice(&(field3.len() as u32).to_ne_bytes());
+        bytes.extend_from_slice(field3.as_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_wire_format_is_big_endian_with_header() {
+        let data = Data {
+            field1: 0x0102_0304,
+            field2: 0x0506,
+            field3: "hi".to_string(),
+        };
+        let serialized = data.serialize().unwrap();
+        assert_eq!(
+            serialized,
+            [0xDA, 0x7A, 1, 1, 2, 3, 4, 5, 6, 0, 0, 0, 2, b'h', b'i']
+        );
+    }
+
+    #[test]
+    fn test_unknown_version_is_rejected() {
+        let data = Data {
+            field1: 1,
+            field2: 2,
+            field3: "x".to_string(),
+        };
+        let mut serialized = data.serialize().unwrap();
+        serialized[2] = 9;
+        let error = Data::deserialize(&mut Cursor::new(serialized.as_slice())).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "unsupported wire format version 9 (expected 1)");
+
+        let error =
+            Data::deserialize_with_legacy(&mut Cursor::new(serialized.as_slice())).unwrap_err();
+        assert_eq!(error.to_string(), "unsupported wire format version 9 (expected 1)");
+    }
+
+    #[test]
+    fn test_legacy_frames_need_opt_in() {
+        let legacy = legacy_frame(42, 1337, "old client");
+        assert!(Data::deserialize(&mut Cursor::new(legacy.as_slice())).is_err());
+
+        let decoded = Data::deserialize_with_legacy(&mut Cursor::new(legacy.as_slice())).unwrap();
+        assert_eq!(decoded.field1, 42);
+        assert_eq!(decoded.field2, 1337);
+        assert_eq!(decoded.field3, "old client");
+    }
+
+    #[test]
+    fn test_deserialize_with_legacy_reads_current_frames() {
+        let data = Data {
+            field1: 7,
+            field2: 8,
+            field3: "new client".to_string(),
+        };
+        let serialized = data.serialize().unwrap();
+        let decoded =
+            Data::deserialize_with_legacy(&mut Cursor::new(serialized.as_slice())).unwrap();
+        assert_eq!(decoded.field3, "new client");
+    }
+
+    #[test]
+    fn test_data_ref_borrows_from_the_input() {
+        let data = Data {
+            field1: 3,
+            field2: 4,
+            field3: "borrowed".to_string(),
+        };
+        let serialized = data.serialize().unwrap();
+        let mut cursor = Cursor::new(serialized.as_slice());
+        let borrowed = DataRef::deserialize(&mut cursor).unwrap();
+        assert_eq!(cursor.position() as usize, serialized.len());
+        assert_eq!(borrowed.field1, 3);
+        assert_eq!(borrowed.field2, 4);
+        assert_eq!(borrowed.field3, "borrowed");
+        // field3 points into the frame rather than at a copy.
+        assert_eq!(
+            borrowed.field3.as_ptr(),
+            serialized[serialized.len() - "borrowed".len()..].as_ptr()
+        );
+    }
+
+    #[test]
+    fn test_data_ref_to_owned_and_back() {
+        let data = Data {
+            field1: 5,
+            field2: 6,
+            field3: "owned".to_string(),
+        };
+        let borrowed = DataRef::from(&data);
+        let owned = borrowed.to_owned();
+        assert_eq!(owned.field1, data.field1);
+        assert_eq!(owned.field2, data.field2);
+        assert_eq!(owned.field3, data.field3);
+
+        // Both encode to the same fields.
+        assert_eq!(borrowed.to_bytes().unwrap(), data.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_data_ref_legacy_frames() {
+        let legacy = legacy_frame(42, 1337, "old client");
+        assert!(DataRef::deserialize(&mut Cursor::new(legacy.as_slice())).is_err());
+        let borrowed = DataRef::deserialize_with_legacy(&mut Cursor::new(legacy.as_slice())).unwrap();
+        assert_eq!(
+            borrowed,
+            DataRef {
+                field1: 42,
+                field2: 1337,
+                field3: "old client"
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_into_reuses_the_buffer() {
+        let first = Data {
+            field1: 1,
+            field2: 2,
+            field3: "a longer first message".to_string(),
+        };
+        let second = Data {
+            field1: 3,
+            field2: 4,
+            field3: "short".to_string(),
+        };
+        let mut buffer = Vec::new();
+        first.serialize_into(&mut buffer).unwrap();
+        assert_eq!(buffer, first.serialize().unwrap());
+        assert_eq!(buffer.len(), first.encoded_len());
+
+        let capacity = buffer.capacity();
+        buffer.clear();
+        second.serialize_into(&mut buffer).unwrap();
+        assert_eq!(buffer, second.serialize().unwrap());
+        assert_eq!(buffer.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_encode_to_slice() {
+        let data = Data {
+            field1: 7,
+            field2: 8,
+            field3: "slice".to_string(),
+        };
+        let mut buf = [0xffu8; 64];
+        let len = data.encode_to_slice(&mut buf).unwrap();
+        assert_eq!(len, data.encoded_len());
+        assert_eq!(&buf[..len], data.serialize().unwrap().as_slice());
+        assert!(buf[len..].iter().all(|&b| b == 0xff));
+
+        // The trait methods see the same frame, header included.
+        assert_eq!(data.to_bytes().unwrap(), data.serialize().unwrap());
+        assert_eq!(DataRef::from(&data).encoded_len(), len);
+        assert_eq!(DataRef::from_bytes(&buf[..len]).unwrap(), DataRef::from(&data));
+    }
+
+    #[test]
+    fn test_encode_to_short_slice_writes_nothing() {
+        let data = Data {
+            field1: 7,
+            field2: 8,
+            field3: "slice".to_string(),
+        };
+        let mut buf = [0u8; 10];
+        let err = data.encode_to_slice(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+        assert_eq!(
+            err.to_string(),
+            format!("needed {} bytes but the buffer only has 10", data.encoded_len())
+        );
+        assert_eq!(buf, [0u8; 10]);
+    }
 }
@@ -0,0 +1,144 @@
+use crate::data::limits::LimitExceeded;
+use std::{error::Error, fmt, io};
+
+/// Why decoding failed. Offsets count from the start of the input.
+///
+/// Converts into an [`io::Error`] of the matching kind for callers that deal in
+/// `io::Result`. The `DecodeError` stays inside as its payload, where
+/// [`DecodeError::from_io`] can find it again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended `needed` bytes into a value at `offset` that had only `remaining`.
+    Truncated {
+        offset: usize,
+        needed: usize,
+        remaining: usize,
+    },
+    /// A string was not UTF-8. `field` is the innermost named field it was decoded for.
+    InvalidUtf8 {
+        field: Option<&'static str>,
+        offset: usize,
+    },
+    /// A frame did not start with the magic bytes.
+    MissingHeader,
+    BadVersion {
+        found: u8,
+        expected: u8,
+    },
+    LimitExceeded(LimitExceeded),
+    /// An enum tag that no variant of `type_name` uses.
+    UnknownTag {
+        type_name: &'static str,
+        tag: u32,
+        offset: usize,
+    },
+    TrailingBytes {
+        count: usize,
+    },
+    MissingField {
+        field: &'static str,
+        tag: u32,
+    },
+    DuplicateField {
+        field: &'static str,
+        tag: u32,
+    },
+    /// Any other malformed value, such as a bool byte other than 0 or 1.
+    Invalid {
+        offset: usize,
+        reason: String,
+    },
+}
+
+impl DecodeError {
+    pub fn invalid(offset: usize, reason: impl Into<String>) -> Self {
+        DecodeError::Invalid {
+            offset,
+            reason: reason.into(),
+        }
+    }
+
+    /// The [`io::ErrorKind`] this converts to.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            DecodeError::Truncated { .. } => io::ErrorKind::UnexpectedEof,
+            _ => io::ErrorKind::InvalidData,
+        }
+    }
+
+    /// Names the field an invalid string was decoded for, unless a nested field already did.
+    pub fn in_field(mut self, name: &'static str) -> Self {
+        if let DecodeError::InvalidUtf8 { field: field @ None, .. } = &mut self {
+            *field = Some(name);
+        }
+        self
+    }
+
+    pub fn from_io(err: &io::Error) -> Option<&DecodeError> {
+        err.get_ref()?.downcast_ref()
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Truncated {
+                offset,
+                needed,
+                remaining,
+            } => write!(
+                f,
+                "needed {} bytes at offset {} but only {} remain",
+                needed, offset, remaining
+            ),
+            DecodeError::InvalidUtf8 {
+                field: Some(field),
+                offset,
+            } => write!(f, "Invalid UTF-8 in field {} at offset {}", field, offset),
+            DecodeError::InvalidUtf8 {
+                field: None,
+                offset,
+            } => write!(f, "Invalid UTF-8 at offset {}", offset),
+            DecodeError::MissingHeader => {
+                write!(f, "missing frame header; is this a legacy native-endian frame?")
+            }
+            DecodeError::BadVersion { found, expected } => write!(
+                f,
+                "unsupported wire format version {} (expected {})",
+                found, expected
+            ),
+            DecodeError::LimitExceeded(exceeded) => exceeded.fmt(f),
+            DecodeError::UnknownTag {
+                type_name,
+                tag,
+                offset,
+            } => write!(f, "unknown {} tag {} at offset {}", type_name, tag, offset),
+            DecodeError::TrailingBytes { count } => {
+                write!(f, "{} trailing bytes after value", count)
+            }
+            DecodeError::MissingField { field, tag } => {
+                write!(f, "required field {} (tag {}) is missing", field, tag)
+            }
+            DecodeError::DuplicateField { field, tag } => {
+                write!(f, "field {} (tag {}) appears more than once", field, tag)
+            }
+            DecodeError::Invalid { offset, reason } => {
+                write!(f, "{} at offset {}", reason, offset)
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+impl From<LimitExceeded> for DecodeError {
+    fn from(err: LimitExceeded) -> Self {
+        DecodeError::LimitExceeded(err)
+    }
+}
+
+impl From<DecodeError> for io::Error {
+    fn from(err: DecodeError) -> Self {
+        io::Error::new(err.kind(), err)
+    }
+}
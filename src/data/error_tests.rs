@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod tests {
+    use crate::data::codec::{Decode, Encode};
+    use crate::data::data_layer::{Data, MAGIC, VERSION};
+    use crate::data::error::DecodeError;
+    use crate::data::limits::{DecodeLimits, Limit, LimitExceeded};
+    use std::io::{self, Cursor, ErrorKind};
+
+    fn frame(field3: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&1u32.to_be_bytes());
+        bytes.extend_from_slice(&2u16.to_be_bytes());
+        bytes.extend_from_slice(&(field3.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(field3);
+        bytes
+    }
+
+    #[test]
+    fn test_truncation_reports_offset_and_bytes_needed() {
+        let bytes = frame(b"hello");
+        let err = Data::deserialize(&mut Cursor::new(&bytes[..bytes.len() - 2])).unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::Truncated {
+                offset: 13,
+                needed: 5,
+                remaining: 3
+            }
+        );
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_invalid_utf8_names_the_field() {
+        let bytes = frame(&[0xFF, 0xFE]);
+        let err = Data::deserialize(&mut Cursor::new(&bytes[..])).unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::InvalidUtf8 {
+                field: Some("field3"),
+                offset: 13
+            }
+        );
+        assert_eq!(err.to_string(), "Invalid UTF-8 in field field3 at offset 13");
+    }
+
+    #[test]
+    fn test_legacy_invalid_utf8_names_the_field() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&1u32.to_ne_bytes());
+        bytes.extend_from_slice(&2u16.to_ne_bytes());
+        bytes.extend_from_slice(&1u32.to_ne_bytes());
+        bytes.push(0xFF);
+        let err = Data::deserialize_with_legacy(&mut Cursor::new(&bytes[..])).unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::InvalidUtf8 {
+                field: Some("field3"),
+                offset: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_bad_version_and_missing_header() {
+        let mut bytes = frame(b"x");
+        bytes[2] = 7;
+        let err = Data::deserialize(&mut Cursor::new(&bytes[..])).unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::BadVersion {
+                found: 7,
+                expected: VERSION
+            }
+        );
+
+        let err = Data::deserialize(&mut Cursor::new(&bytes[2..])).unwrap_err();
+        assert_eq!(err, DecodeError::MissingHeader);
+    }
+
+    #[test]
+    fn test_limit_exceeded() {
+        let bytes = frame(b"hello");
+        let limits = DecodeLimits::default().max_string_len(2);
+        let err = Data::deserialize_with_limits(&mut Cursor::new(&bytes[..]), limits).unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::LimitExceeded(LimitExceeded {
+                limit: Limit::StringLength,
+                max: 2,
+                actual: 5,
+                offset: 9
+            })
+        );
+    }
+
+    #[test]
+    fn test_offsets_inside_nested_values_are_absolute() {
+        let bytes = (7u8, vec![String::from("ok"), String::from("x")]).to_bytes().unwrap();
+        let mut corrupt = bytes.clone();
+        let last = corrupt.len() - 1;
+        corrupt[last] = 0xFF;
+        let err = <(u8, Vec<String>)>::from_bytes(&corrupt).unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::InvalidUtf8 {
+                field: None,
+                offset: last
+            }
+        );
+    }
+
+    #[test]
+    fn test_converts_to_io_error() {
+        let err: io::Error = DecodeError::Truncated {
+            offset: 1,
+            needed: 4,
+            remaining: 0,
+        }
+        .into();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(err.to_string(), "needed 4 bytes at offset 1 but only 0 remain");
+        assert!(matches!(
+            DecodeError::from_io(&err),
+            Some(DecodeError::Truncated { offset: 1, .. })
+        ));
+
+        let err: io::Error = DecodeError::MissingHeader.into();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // `?` converts in functions that return io::Result.
+        fn decode_u32(bytes: &[u8]) -> io::Result<u32> {
+            Ok(u32::from_bytes(bytes)?)
+        }
+        assert_eq!(decode_u32(&[0, 0, 0, 9]).unwrap(), 9);
+        let err = decode_u32(&[0, 0, 0, 9, 1]).unwrap_err();
+        assert_eq!(
+            DecodeError::from_io(&err),
+            Some(&DecodeError::TrailingBytes { count: 1 })
+        );
+    }
+}
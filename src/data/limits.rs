@@ -0,0 +1,116 @@
+use crate::data::error::DecodeError;
+use std::{error::Error, fmt, io};
+
+/// Bounds a [`Decoder`](crate::data::codec::Decoder) enforces on untrusted input. Lengths are
+/// checked as soon as their prefix is read, before anything is allocated for them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Longest string, in bytes.
+    pub max_string_len: usize,
+    /// Most elements in one vector or map.
+    pub max_collection_len: usize,
+    /// Deepest nesting of containers, structs and enums.
+    pub max_depth: usize,
+    /// Most bytes read from one input.
+    pub max_total_bytes: usize,
+}
+
+impl DecodeLimits {
+    pub const fn unlimited() -> Self {
+        DecodeLimits {
+            max_string_len: usize::MAX,
+            max_collection_len: usize::MAX,
+            max_depth: usize::MAX,
+            max_total_bytes: usize::MAX,
+        }
+    }
+
+    pub const fn max_string_len(mut self, max: usize) -> Self {
+        self.max_string_len = max;
+        self
+    }
+
+    pub const fn max_collection_len(mut self, max: usize) -> Self {
+        self.max_collection_len = max;
+        self
+    }
+
+    pub const fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = max;
+        self
+    }
+
+    pub const fn max_total_bytes(mut self, max: usize) -> Self {
+        self.max_total_bytes = max;
+        self
+    }
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        DecodeLimits {
+            max_string_len: 16 << 20,
+            max_collection_len: 1 << 20,
+            max_depth: 64,
+            max_total_bytes: 64 << 20,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    StringLength,
+    CollectionLength,
+    Depth,
+    TotalBytes,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Limit::StringLength => "string length",
+            Limit::CollectionLength => "collection length",
+            Limit::Depth => "nesting depth",
+            Limit::TotalBytes => "total bytes",
+        })
+    }
+}
+
+/// Decoding stopped because the input went over one of its [`DecodeLimits`]. Reported as
+/// [`DecodeError::LimitExceeded`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LimitExceeded {
+    pub limit: Limit,
+    pub max: usize,
+    pub actual: usize,
+    /// Where in the input the offending length or value starts.
+    pub offset: usize,
+}
+
+impl LimitExceeded {
+    /// Finds the limit error inside an [`io::Error`] converted from a [`DecodeError`].
+    pub fn from_io(err: &io::Error) -> Option<&LimitExceeded> {
+        match DecodeError::from_io(err)? {
+            DecodeError::LimitExceeded(exceeded) => Some(exceeded),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} at offset {} exceeds the limit of {}",
+            self.limit, self.actual, self.offset, self.max
+        )
+    }
+}
+
+impl Error for LimitExceeded {}
+
+impl From<LimitExceeded> for io::Error {
+    fn from(err: LimitExceeded) -> Self {
+        DecodeError::from(err).into()
+    }
+}
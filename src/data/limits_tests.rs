@@ -0,0 +1,179 @@
+#[cfg(test)]
+mod tests {
+    use crate::data::codec::{Decode, Encode};
+    use crate::data::data_layer::Data;
+    use crate::data::error::DecodeError;
+    use crate::data::limits::{DecodeLimits, Limit, LimitExceeded};
+    use crate::data::varint::VarLen;
+    use std::collections::BTreeMap;
+    use std::io::{Cursor, ErrorKind};
+
+    fn exceeded(err: &DecodeError) -> &LimitExceeded {
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        match err {
+            DecodeError::LimitExceeded(exceeded) => exceeded,
+            _ => panic!("not a limit error: {}", err),
+        }
+    }
+
+    fn limit_of(err: &DecodeError) -> Limit {
+        exceeded(err).limit
+    }
+
+    #[test]
+    fn test_string_length_is_checked_before_reading() {
+        // Claims a 4 GiB string in a 4-byte input.
+        let err = String::from_bytes(&[0xff, 0xff, 0xff, 0xff]).unwrap_err();
+        let exceeded = exceeded(&err);
+        assert_eq!(exceeded.limit, Limit::StringLength);
+        assert_eq!(exceeded.actual, u32::MAX as usize);
+        assert_eq!(exceeded.offset, 0);
+
+        let limits = DecodeLimits::default().max_string_len(3);
+        let bytes = "abcd".to_bytes().unwrap();
+        let err = String::from_bytes_with_limits(&bytes, limits).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "string length 4 at offset 0 exceeds the limit of 3"
+        );
+        assert_eq!(
+            String::from_bytes_with_limits(&bytes[..], limits.max_string_len(4)).unwrap(),
+            "abcd"
+        );
+    }
+
+    #[test]
+    fn test_varint_string_length_is_checked() {
+        let bytes = VarLen(String::from("abcd")).to_bytes().unwrap();
+        let limits = DecodeLimits::default().max_string_len(3);
+        let err = VarLen::<String>::from_bytes_with_limits(&bytes, limits).unwrap_err();
+        assert_eq!(limit_of(&err), Limit::StringLength);
+    }
+
+    #[test]
+    fn test_collection_length_is_checked() {
+        let limits = DecodeLimits::default().max_collection_len(2);
+        let bytes = vec![1u8, 2, 3].to_bytes().unwrap();
+        let err = Vec::<u8>::from_bytes_with_limits(&bytes, limits).unwrap_err();
+        assert_eq!(limit_of(&err), Limit::CollectionLength);
+
+        let map: BTreeMap<u8, u8> = [(1, 1), (2, 2), (3, 3)].into();
+        let bytes = map.to_bytes().unwrap();
+        let err = BTreeMap::<u8, u8>::from_bytes_with_limits(&bytes, limits).unwrap_err();
+        assert_eq!(limit_of(&err), Limit::CollectionLength);
+    }
+
+    #[test]
+    fn test_varint_collection_length_is_checked() {
+        let limits = DecodeLimits::default().max_collection_len(2);
+        let bytes = VarLen(vec![1u8, 2, 3]).to_bytes().unwrap();
+        let err = VarLen::<Vec<u8>>::from_bytes_with_limits(&bytes, limits).unwrap_err();
+        assert_eq!(limit_of(&err), Limit::CollectionLength);
+
+        // Claims 4096 elements within the limit, each 4 KiB in memory but one byte on the
+        // wire, and ends after the first.
+        let bytes = [0x80, 0x20, 0x00];
+        let err = VarLen::<Vec<Option<[u64; 512]>>>::from_bytes(&bytes).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_depth_is_checked() {
+        let nested = vec![vec![vec![1u8]]];
+        let bytes = nested.to_bytes().unwrap();
+        let err = Vec::<Vec<Vec<u8>>>::from_bytes_with_limits(
+            &bytes,
+            DecodeLimits::default().max_depth(2),
+        )
+        .unwrap_err();
+        assert_eq!(limit_of(&err), Limit::Depth);
+        let decoded = Vec::<Vec<Vec<u8>>>::from_bytes_with_limits(
+            &bytes,
+            DecodeLimits::default().max_depth(3),
+        )
+        .unwrap();
+        assert_eq!(decoded, nested);
+    }
+
+    #[test]
+    fn test_depth_counts_declared_types() {
+        crate::wire_enum! {
+            enum Tree {
+                Leaf = 0,
+                Node(Vec<Tree>) = 1,
+            }
+        }
+
+        let mut tree = Tree::Leaf;
+        for _ in 0..10 {
+            tree = Tree::Node(vec![tree]);
+        }
+        let bytes = tree.to_bytes().unwrap();
+        // Each level is the enum plus its vector.
+        assert!(
+            Tree::from_bytes_with_limits(&bytes, DecodeLimits::default().max_depth(21)).is_ok()
+        );
+        let err = Tree::from_bytes_with_limits(&bytes, DecodeLimits::default().max_depth(20))
+            .unwrap_err();
+        assert_eq!(limit_of(&err), Limit::Depth);
+    }
+
+    #[test]
+    fn test_total_bytes_is_checked() {
+        let bytes = [1u32, 2, 3].to_bytes().unwrap();
+        let limits = DecodeLimits::default().max_total_bytes(8);
+        let err = <[u32; 3]>::from_bytes_with_limits(&bytes, limits).unwrap_err();
+        let exceeded = exceeded(&err);
+        assert_eq!(exceeded.limit, Limit::TotalBytes);
+        assert_eq!(exceeded.actual, 12);
+    }
+
+    #[test]
+    fn test_tlv_fields_inherit_limits() {
+        crate::tlv_struct! {
+            struct Note {
+                #[tlv(1)]
+                text: String,
+            }
+        }
+
+        let bytes = Note {
+            text: String::from("hello"),
+        }
+        .to_bytes()
+        .unwrap();
+        let err = Note::from_bytes_with_limits(&bytes, DecodeLimits::default().max_string_len(4))
+            .unwrap_err();
+        assert_eq!(limit_of(&err), Limit::StringLength);
+    }
+
+    #[test]
+    fn test_data_deserialize_applies_limits() {
+        let data = Data {
+            field1: 1,
+            field2: 2,
+            field3: String::from("too long"),
+        };
+        let bytes = data.serialize().unwrap();
+        let limits = DecodeLimits::default().max_string_len(4);
+        let err = Data::deserialize_with_limits(&mut Cursor::new(&bytes[..]), limits).unwrap_err();
+        assert_eq!(limit_of(&err), Limit::StringLength);
+    }
+
+    #[test]
+    fn test_legacy_frame_cannot_force_a_huge_allocation() {
+        // field1, field2, then a length of u32::MAX with no payload behind it.
+        let mut frame = Vec::new();
+        frame.extend_from_slice(&7u32.to_ne_bytes());
+        frame.extend_from_slice(&1u16.to_ne_bytes());
+        frame.extend_from_slice(&u32::MAX.to_ne_bytes());
+        let err = Data::deserialize_with_legacy(&mut Cursor::new(&frame[..])).unwrap_err();
+        assert_eq!(limit_of(&err), Limit::StringLength);
+
+        // Within the limit but longer than the frame.
+        let mut frame = frame[..6].to_vec();
+        frame.extend_from_slice(&1024u32.to_ne_bytes());
+        let err = Data::deserialize_with_legacy(&mut Cursor::new(&frame[..])).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+}
@@ -1,4 +1,25 @@
+pub mod codec;
 pub mod data_layer;
+pub mod error;
+pub mod limits;
+pub mod stream;
+pub mod tlv;
+pub mod varint;
+pub mod wire;
 
+#[cfg(test)]
+mod codec_tests;
 #[cfg(test)]
 mod data_layer_tests;
+#[cfg(test)]
+mod error_tests;
+#[cfg(test)]
+mod limits_tests;
+#[cfg(test)]
+mod stream_tests;
+#[cfg(test)]
+mod tlv_tests;
+#[cfg(test)]
+mod varint_tests;
+#[cfg(test)]
+mod wire_tests;
@@ -0,0 +1,100 @@
+use crate::data::codec::{Decode, Decoder};
+use crate::data::error::DecodeError;
+use crate::data::limits::DecodeLimits;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Decoded<T> {
+    Complete(T),
+    /// At least this many more bytes are needed before the next value can be decoded.
+    NeedMore(usize),
+}
+
+/// Decodes a stream of values from bytes that arrive in arbitrary chunks, such as reads from
+/// a socket. Bytes are buffered until a whole value is available, and whatever follows it
+/// stays buffered for the next one.
+///
+/// A value that is cut off at the end of the buffer is reported as [`Decoded::NeedMore`];
+/// anything else that fails to decode is an error. After an error the buffer is left as it
+/// was, since there is no telling where the next value starts.
+///
+/// ```
+/// use std_async::data::{codec::Encode, stream::{Decoded, StreamDecoder}};
+///
+/// let bytes = 0x0102_0304u32.to_bytes().unwrap();
+/// let mut decoder = StreamDecoder::<u32>::new();
+/// decoder.feed(&bytes[..1]);
+/// assert_eq!(decoder.decode().unwrap(), Decoded::NeedMore(3));
+/// decoder.feed(&bytes[1..]);
+/// assert_eq!(decoder.decode().unwrap(), Decoded::Complete(0x0102_0304));
+/// ```
+pub struct StreamDecoder<T> {
+    buffer: Vec<u8>,
+    limits: DecodeLimits,
+    decode: fn(&mut Decoder<'_>) -> Result<T, DecodeError>,
+    /// Buffer length the last attempt needed, so that partial values aren't decoded again
+    /// for every chunk.
+    wanted: usize,
+}
+
+impl<T: for<'de> Decode<'de>> StreamDecoder<T> {
+    pub fn new() -> Self {
+        StreamDecoder::with_decode_fn(|decoder| T::decode(decoder))
+    }
+}
+
+impl<T: for<'de> Decode<'de>> Default for StreamDecoder<T> {
+    fn default() -> Self {
+        StreamDecoder::new()
+    }
+}
+
+impl<T> StreamDecoder<T> {
+    /// Decodes each value with `decode` instead of [`Decode::decode`], for framed formats
+    /// such as [`Data::decode_frame`](crate::data::data_layer::Data::decode_frame).
+    pub fn with_decode_fn(decode: fn(&mut Decoder<'_>) -> Result<T, DecodeError>) -> Self {
+        StreamDecoder {
+            buffer: Vec::new(),
+            limits: DecodeLimits::default(),
+            decode,
+            wanted: 0,
+        }
+    }
+
+    /// Limits for each value. `max_total_bytes` caps how much one value may buffer.
+    pub fn limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Bytes received but not yet decoded.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Decodes the next value if all of it has arrived.
+    pub fn decode(&mut self) -> Result<Decoded<T>, DecodeError> {
+        if self.buffer.len() < self.wanted {
+            return Ok(Decoded::NeedMore(self.wanted - self.buffer.len()));
+        }
+        let mut decoder = Decoder::with_limits(&self.buffer, self.limits);
+        match (self.decode)(&mut decoder) {
+            Ok(value) => {
+                let used = decoder.position();
+                self.buffer.drain(..used);
+                self.wanted = 0;
+                Ok(Decoded::Complete(value))
+            }
+            // Only running off the end of the buffer means more input could help. A value
+            // that overruns its own length prefix fails as invalid inside `Decoder::split`.
+            Err(DecodeError::Truncated { offset, needed, .. }) => {
+                self.wanted = offset + needed;
+                Ok(Decoded::NeedMore(self.wanted - self.buffer.len()))
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(test)]
+mod tests {
+    use crate::data::codec::Encode;
+    use crate::data::data_layer::Data;
+    use crate::data::error::DecodeError;
+    use crate::data::limits::{DecodeLimits, Limit};
+    use crate::data::stream::{Decoded, StreamDecoder};
+
+    fn data(n: u32) -> Data {
+        Data {
+            field1: n,
+            field2: n as u16,
+            field3: format!("message {}", n),
+        }
+    }
+
+    fn complete<T>(decoded: Decoded<T>) -> T {
+        match decoded {
+            Decoded::Complete(value) => value,
+            Decoded::NeedMore(n) => panic!("still needs {} bytes", n),
+        }
+    }
+
+    #[test]
+    fn test_empty_decoder_needs_more() {
+        let mut decoder = StreamDecoder::<u32>::new();
+        assert_eq!(decoder.decode().unwrap(), Decoded::NeedMore(4));
+    }
+
+    #[test]
+    fn test_one_byte_at_a_time() {
+        let bytes = data(7).serialize().unwrap();
+        let mut decoder = StreamDecoder::with_decode_fn(Data::decode_frame);
+        for &byte in &bytes[..bytes.len() - 1] {
+            decoder.feed(&[byte]);
+            assert!(matches!(decoder.decode().unwrap(), Decoded::NeedMore(_)));
+        }
+        decoder.feed(&bytes[bytes.len() - 1..]);
+        let message = complete(decoder.decode().unwrap());
+        assert_eq!(message.field1, 7);
+        assert_eq!(message.field3, "message 7");
+        assert!(decoder.buffered().is_empty());
+    }
+
+    #[test]
+    fn test_need_more_counts_missing_bytes() {
+        let bytes = data(1).serialize().unwrap();
+        let mut decoder = StreamDecoder::with_decode_fn(Data::decode_frame);
+        // Header, field1, field2 and the string length, but none of the string.
+        decoder.feed(&bytes[..13]);
+        assert!(matches!(decoder.decode().unwrap(), Decoded::NeedMore(9)));
+        decoder.feed(&bytes[13..15]);
+        assert!(matches!(decoder.decode().unwrap(), Decoded::NeedMore(7)));
+        decoder.feed(&bytes[15..]);
+        assert_eq!(complete(decoder.decode().unwrap()).field1, 1);
+    }
+
+    #[test]
+    fn test_leftover_bytes_start_the_next_value() {
+        let mut bytes = data(1).serialize().unwrap();
+        bytes.extend(data(2).serialize().unwrap());
+        let first_len = data(1).serialize().unwrap().len();
+
+        let mut decoder = StreamDecoder::with_decode_fn(Data::decode_frame);
+        decoder.feed(&bytes[..first_len + 5]);
+        assert_eq!(complete(decoder.decode().unwrap()).field1, 1);
+        assert_eq!(decoder.buffered(), &bytes[first_len..first_len + 5]);
+        assert!(matches!(decoder.decode().unwrap(), Decoded::NeedMore(_)));
+
+        decoder.feed(&bytes[first_len + 5..]);
+        assert_eq!(complete(decoder.decode().unwrap()).field1, 2);
+        assert!(matches!(decoder.decode().unwrap(), Decoded::NeedMore(_)));
+    }
+
+    #[test]
+    fn test_legacy_frames() {
+        let mut legacy = Vec::new();
+        legacy.extend_from_slice(&3u32.to_ne_bytes());
+        legacy.extend_from_slice(&4u16.to_ne_bytes());
+        legacy.extend_from_slice(&2u32.to_ne_bytes());
+        legacy.extend_from_slice(b"ok");
+
+        let mut decoder = StreamDecoder::with_decode_fn(Data::decode_frame_with_legacy);
+        decoder.feed(&legacy[..1]);
+        assert!(matches!(decoder.decode().unwrap(), Decoded::NeedMore(_)));
+        decoder.feed(&legacy[1..]);
+        decoder.feed(&data(5).serialize().unwrap());
+        assert_eq!(complete(decoder.decode().unwrap()).field3, "ok");
+        assert_eq!(complete(decoder.decode().unwrap()).field1, 5);
+    }
+
+    #[test]
+    fn test_malformed_input_is_an_error() {
+        let mut bytes = data(1).serialize().unwrap();
+        bytes[2] = 9;
+        let mut decoder = StreamDecoder::with_decode_fn(Data::decode_frame);
+        decoder.feed(&bytes);
+        assert!(matches!(
+            decoder.decode(),
+            Err(DecodeError::BadVersion { found: 9, .. })
+        ));
+        assert_eq!(decoder.buffered(), bytes.as_slice());
+    }
+
+    #[test]
+    fn test_value_overrunning_its_own_frame_is_an_error() {
+        crate::tlv_struct! {
+            struct Note {
+                #[tlv(1)]
+                id: u32,
+            }
+        }
+
+        // The body is all there and ends where the buffer does, but its tag 1 claims a
+        // two-byte value with one byte left; more input can't fix that.
+        let mut decoder = StreamDecoder::<Note>::new();
+        decoder.feed(&[3, 1, 2, 0]);
+        assert!(matches!(
+            decoder.decode(),
+            Err(DecodeError::Invalid { offset: 3, .. })
+        ));
+        assert_eq!(decoder.buffered(), [3, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_limits_cap_buffered_values() {
+        let mut decoder = StreamDecoder::with_decode_fn(Data::decode_frame)
+            .limits(DecodeLimits::default().max_total_bytes(16));
+        let bytes = data(1).serialize().unwrap();
+        decoder.feed(&bytes[..13]);
+        match decoder.decode() {
+            Err(DecodeError::LimitExceeded(exceeded)) => {
+                assert_eq!(exceeded.limit, Limit::TotalBytes)
+            }
+            other => panic!("expected a limit error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_values_without_a_frame() {
+        let mut decoder = StreamDecoder::<(u8, String)>::new();
+        let bytes = (1u8, String::from("hi")).to_bytes().unwrap();
+        decoder.feed(&bytes[..3]);
+        assert_eq!(decoder.decode().unwrap(), Decoded::NeedMore(2));
+        decoder.feed(&bytes[3..]);
+        assert_eq!(
+            decoder.decode().unwrap(),
+            Decoded::Complete((1, String::from("hi")))
+        );
+    }
+}
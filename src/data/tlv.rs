@@ -0,0 +1,285 @@
+//! Tag-length-value messages, which peers can extend without breaking each other.
+//!
+//! A message is its body length as a [`Varint`] followed by the body, and the body is a
+//! run of fields. Each field is its tag and the length of its value, both varints, then
+//! the value in its [`Encode`] form. Decoders skip the tags they don't know, so fields
+//! can be added as long as old peers don't require them.
+
+use crate::data::codec::{Decode, Decoder, Encode};
+use crate::data::error::DecodeError;
+use crate::data::varint::{Varint, varint_len};
+use std::io::{self, Write};
+
+/// A field a decoder did not recognise, kept so that it survives re-encoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownField {
+    pub tag: u32,
+    pub value: Vec<u8>,
+}
+
+/// Bytes taken by a field with this tag and value length.
+pub fn field_len(tag: u32, value_len: usize) -> usize {
+    varint_len(u128::from(tag)) + varint_len(value_len as u128) + value_len
+}
+
+/// Writes one field.
+pub fn encode_field<T, W>(tag: u32, value: &T, writer: &mut W) -> io::Result<()>
+where
+    T: Encode + ?Sized,
+    W: Write,
+{
+    Varint(tag).encode(writer)?;
+    Varint(value.encoded_len()).encode(writer)?;
+    value.encode(writer)
+}
+
+/// Writes a message header for a body of `body_len` bytes.
+pub fn encode_header<W: Write>(body_len: usize, writer: &mut W) -> io::Result<()> {
+    Varint(body_len).encode(writer)
+}
+
+/// Reads a message header and returns a reader over the fields in its body.
+pub fn decode_fields<'de>(decoder: &mut Decoder<'de>) -> Result<Fields<'de>, DecodeError> {
+    let Varint(len) = Varint::<usize>::decode(decoder)?;
+    Ok(Fields {
+        body: decoder.split(len)?,
+    })
+}
+
+/// The fields of one message body, in the order they were written.
+pub struct Fields<'de> {
+    body: Decoder<'de>,
+}
+
+impl<'de> Fields<'de> {
+    /// The next field's tag and a decoder over its value, or `None` at the end of the body.
+    pub fn next_field(&mut self) -> Result<Option<(u32, Decoder<'de>)>, DecodeError> {
+        if self.body.remaining() == 0 {
+            return Ok(None);
+        }
+        let Varint(tag) = Varint::<u32>::decode(&mut self.body)?;
+        let Varint(len) = Varint::<usize>::decode(&mut self.body)?;
+        Ok(Some((tag, self.body.split(len)?)))
+    }
+}
+
+/// Decodes a field value that must fill its whole slot.
+pub fn decode_value<'de, T: Decode<'de>>(
+    tag: u32,
+    name: &'static str,
+    mut value: Decoder<'de>,
+) -> Result<T, DecodeError> {
+    let decoded = T::decode(&mut value).map_err(|err| err.in_field(name))?;
+    value.finish().map_err(|_| {
+        DecodeError::invalid(
+            value.offset(),
+            format!("field {} (tag {}) has {} unread bytes", name, tag, value.remaining()),
+        )
+    })?;
+    Ok(decoded)
+}
+
+/// The rest of an unrecognised field's value.
+pub fn unknown_field(tag: u32, mut value: Decoder<'_>) -> Result<UnknownField, DecodeError> {
+    let len = value.remaining();
+    Ok(UnknownField {
+        tag,
+        value: value.read_bytes(len)?.to_vec(),
+    })
+}
+
+/// Declares a struct encoded as a tag-length-value message and derives `Debug`, `Encode`
+/// and `Decode` for it. Every field starts with a `#[tlv(..)]` marker:
+///
+/// - `#[tlv(N)]`: a required field with tag `N`. Decoding fails if it is missing.
+/// - `#[tlv(N, optional)]`: takes its `Default` value if it is missing, and is left out
+///   of the encoding while it equals that value. Its type must implement `PartialEq`.
+/// - `#[tlv(unknown)]`: a `Vec<UnknownField>` that collects the fields this version
+///   doesn't know and writes them back out when the message is encoded. Without one,
+///   unknown fields are skipped.
+///
+/// Tags must be unique within a struct.
+///
+/// ```
+/// use std_async::data::tlv::UnknownField;
+///
+/// std_async::tlv_struct! {
+///     pub struct Profile {
+///         #[tlv(1)]
+///         pub id: u32,
+///         #[tlv(2, optional)]
+///         pub nickname: Option<String>,
+///         #[tlv(unknown)]
+///         pub unknown: Vec<UnknownField>,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! tlv_struct {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($body:tt)*
+        }
+    ) => {
+        $crate::tlv_struct!(
+            @parse [$(#[$meta])* $vis struct $name] [] [] $($body)*
+        );
+    };
+
+    // Fields are normalized to `[kind tag field: Type]`, plus the optional unknown field.
+    (@parse $head:tt [$($fields:tt)*] [$($unknown:tt)*]) => {
+        $crate::tlv_struct!(@impl $head [$($fields)*] [$($unknown)*]);
+    };
+    (@parse $head:tt [$($fields:tt)*] [$($unknown:tt)*]
+        #[tlv($tag:literal)] $(#[$fmeta:meta])* $fvis:vis $field:ident : $ty:ty
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::tlv_struct!(
+            @parse $head
+            [$($fields)* [required $tag [$(#[$fmeta])* $fvis] $field: $ty]]
+            [$($unknown)*]
+            $($($rest)*)?
+        );
+    };
+    (@parse $head:tt [$($fields:tt)*] [$($unknown:tt)*]
+        #[tlv($tag:literal, optional)] $(#[$fmeta:meta])* $fvis:vis $field:ident : $ty:ty
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::tlv_struct!(
+            @parse $head
+            [$($fields)* [optional $tag [$(#[$fmeta])* $fvis] $field: $ty]]
+            [$($unknown)*]
+            $($($rest)*)?
+        );
+    };
+    (@parse $head:tt [$($fields:tt)*] []
+        #[tlv(unknown)] $(#[$fmeta:meta])* $fvis:vis $field:ident : $ty:ty
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::tlv_struct!(
+            @parse $head
+            [$($fields)*]
+            [[$(#[$fmeta])* $fvis] $field: $ty]
+            $($($rest)*)?
+        );
+    };
+
+    (@impl
+        [$(#[$meta:meta])* $vis:vis struct $name:ident]
+        [$([$kind:ident $tag:literal [$($fattr:tt)*] $field:ident : $ty:ty])*]
+        [$([$($uattr:tt)*] $unknown:ident : $uty:ty)?]
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        $vis struct $name {
+            $($($fattr)* $field: $ty,)*
+            $($($uattr)* $unknown: $uty,)?
+        }
+
+        impl $name {
+            fn tlv_body_len(&self) -> usize {
+                0 $(+ if $crate::tlv_struct!(@present $kind $ty, self.$field) {
+                    $crate::data::tlv::field_len(
+                        $tag,
+                        $crate::data::codec::Encode::encoded_len(&self.$field),
+                    )
+                } else {
+                    0
+                })*
+                $(+ self.$unknown
+                    .iter()
+                    .map(|field| $crate::data::tlv::field_len(field.tag, field.value.len()))
+                    .sum::<usize>())?
+            }
+        }
+
+        impl $crate::data::codec::Encode for $name {
+            fn encode<W: ::std::io::Write>(&self, writer: &mut W) -> ::std::io::Result<()> {
+                $crate::data::tlv::encode_header(self.tlv_body_len(), writer)?;
+                $(if $crate::tlv_struct!(@present $kind $ty, self.$field) {
+                    $crate::data::tlv::encode_field($tag, &self.$field, writer)?;
+                })*
+                $(for field in &self.$unknown {
+                    $crate::data::codec::Encode::encode(
+                        &$crate::data::varint::Varint(field.tag),
+                        writer,
+                    )?;
+                    $crate::data::codec::Encode::encode(
+                        &$crate::data::varint::Varint(field.value.len()),
+                        writer,
+                    )?;
+                    writer.write_all(&field.value)?;
+                })?
+                ::std::result::Result::Ok(())
+            }
+
+            fn encoded_len(&self) -> usize {
+                let body_len = self.tlv_body_len();
+                $crate::data::varint::varint_len(body_len as u128) + body_len
+            }
+        }
+
+        impl<'de> $crate::data::codec::Decode<'de> for $name {
+            fn decode(
+                decoder: &mut $crate::data::codec::Decoder<'de>,
+            ) -> ::std::result::Result<Self, $crate::data::error::DecodeError> {
+                decoder.nested(|decoder| {
+                    let mut fields = $crate::data::tlv::decode_fields(decoder)?;
+                    $(let mut $field: ::std::option::Option<$ty> = ::std::option::Option::None;)*
+                    $(let mut $unknown: $uty = ::std::vec::Vec::new();)?
+                    while let ::std::option::Option::Some((tag, value)) = fields.next_field()? {
+                        match tag {
+                            $($tag => {
+                                if $field.is_some() {
+                                    return ::std::result::Result::Err(
+                                        $crate::data::error::DecodeError::DuplicateField {
+                                            field: stringify!($field),
+                                            tag: $tag,
+                                        },
+                                    );
+                                }
+                                $field = ::std::option::Option::Some(
+                                    $crate::data::tlv::decode_value($tag, stringify!($field), value)?,
+                                );
+                            })*
+                            _ => {
+                                $($unknown.push($crate::data::tlv::unknown_field(tag, value)?);)?
+                            }
+                        }
+                    }
+                    ::std::result::Result::Ok($name {
+                        $($field: $crate::tlv_struct!(@finish $kind $tag $field),)*
+                        $($unknown,)?
+                    })
+                })
+            }
+        }
+    };
+
+    // Whether a field is written at all. Decoding an absent optional field gives back its
+    // default, so writing the default would only cost bytes.
+    (@present required $ty:ty, $value:expr) => {
+        true
+    };
+    (@present optional $ty:ty, $value:expr) => {
+        $value != <$ty as ::std::default::Default>::default()
+    };
+
+    (@finish required $tag:literal $field:ident) => {
+        match $field {
+            ::std::option::Option::Some(value) => value,
+            ::std::option::Option::None => {
+                return ::std::result::Result::Err(
+                    $crate::data::error::DecodeError::MissingField {
+                        field: stringify!($field),
+                        tag: $tag,
+                    },
+                );
+            }
+        }
+    };
+    (@finish optional $tag:literal $field:ident) => {
+        $field.unwrap_or_default()
+    };
+}
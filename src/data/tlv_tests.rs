@@ -0,0 +1,213 @@
+#[cfg(test)]
+mod tests {
+    use crate::data::codec::{Decode, Encode};
+    use crate::data::error::DecodeError;
+    use crate::data::tlv::UnknownField;
+    use std::io::ErrorKind;
+
+    crate::tlv_struct! {
+        #[derive(Clone, PartialEq)]
+        struct ProfileV1 {
+            #[tlv(1)]
+            id: u32,
+            #[tlv(2, optional)]
+            name: String,
+        }
+    }
+
+    crate::tlv_struct! {
+        #[derive(Clone, PartialEq)]
+        struct ProfileV2 {
+            #[tlv(1)]
+            id: u32,
+            #[tlv(2, optional)]
+            name: String,
+            #[tlv(3, optional)]
+            tags: Vec<String>,
+            #[tlv(4)]
+            score: i16,
+        }
+    }
+
+    crate::tlv_struct! {
+        #[derive(Clone, PartialEq)]
+        struct ProfileRelay {
+            #[tlv(1)]
+            id: u32,
+            #[tlv(unknown)]
+            unknown: Vec<UnknownField>,
+        }
+    }
+
+    fn v2() -> ProfileV2 {
+        ProfileV2 {
+            id: 7,
+            name: String::from("ada"),
+            tags: vec![String::from("x")],
+            score: -4,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let value = v2();
+        let bytes = value.to_bytes().unwrap();
+        assert_eq!(bytes.len(), value.encoded_len());
+        assert_eq!(ProfileV2::from_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_field_layout() {
+        let bytes = ProfileV1 {
+            id: 1,
+            name: String::from("a"),
+        }
+        .to_bytes()
+        .unwrap();
+        // Body length, then tag 1 with a 4-byte u32 and tag 2 with a 5-byte string.
+        assert_eq!(bytes, [13, 1, 4, 0, 0, 0, 1, 2, 5, 0, 0, 0, 1, b'a']);
+    }
+
+    #[test]
+    fn test_default_optional_fields_are_not_encoded() {
+        let value = ProfileV1 {
+            id: 1,
+            name: String::new(),
+        };
+        let bytes = value.to_bytes().unwrap();
+        // Only tag 1; the empty name is what a decoder fills in anyway.
+        assert_eq!(bytes, [6, 1, 4, 0, 0, 0, 1]);
+        assert_eq!(bytes.len(), value.encoded_len());
+        assert_eq!(ProfileV1::from_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_unknown_fields_are_skipped() {
+        let bytes = v2().to_bytes().unwrap();
+        let old = ProfileV1::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            old,
+            ProfileV1 {
+                id: 7,
+                name: String::from("ada")
+            }
+        );
+    }
+
+    #[test]
+    fn test_missing_optional_fields_take_defaults() {
+        crate::tlv_struct! {
+            struct Lenient {
+                #[tlv(1)]
+                id: u32,
+                #[tlv(3, optional)]
+                tags: Vec<String>,
+                #[tlv(9, optional)]
+                retries: Option<u8>,
+            }
+        }
+
+        let bytes = ProfileV1 {
+            id: 3,
+            name: String::new(),
+        }
+        .to_bytes()
+        .unwrap();
+        let decoded = Lenient::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.id, 3);
+        assert!(decoded.tags.is_empty());
+        assert_eq!(decoded.retries, None);
+    }
+
+    #[test]
+    fn test_missing_required_field_is_an_error() {
+        let bytes = ProfileV1 {
+            id: 3,
+            name: String::from("b"),
+        }
+        .to_bytes()
+        .unwrap();
+        let err = ProfileV2::from_bytes(&bytes).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "required field score (tag 4) is missing");
+    }
+
+    #[test]
+    fn test_unknown_fields_can_be_preserved() {
+        let original = v2();
+        let relayed = ProfileRelay::from_bytes(&original.to_bytes().unwrap()).unwrap();
+        assert_eq!(relayed.id, 7);
+        assert_eq!(
+            relayed.unknown.iter().map(|field| field.tag).collect::<Vec<_>>(),
+            [2, 3, 4]
+        );
+
+        let bytes = relayed.to_bytes().unwrap();
+        assert_eq!(bytes.len(), relayed.encoded_len());
+        assert_eq!(ProfileV2::from_bytes(&bytes).unwrap(), original);
+    }
+
+    #[test]
+    fn test_duplicate_field_is_an_error() {
+        // Tag 1 twice.
+        let bytes = [12, 1, 4, 0, 0, 0, 1, 1, 4, 0, 0, 0, 2];
+        let err = ProfileV1::from_bytes(&bytes).unwrap_err();
+        assert_eq!(err.to_string(), "field id (tag 1) appears more than once");
+    }
+
+    #[test]
+    fn test_short_value_is_invalid_at_its_offset() {
+        // Tag 1 with a 2-byte value where a u32 is expected.
+        let bytes = [4, 1, 2, 0, 1];
+        let err = ProfileV1::from_bytes(&bytes).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            err,
+            DecodeError::invalid(3, "needed 4 bytes but the enclosing value has only 2 left")
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8_names_the_field() {
+        // Tag 2 with a one-byte string that isn't UTF-8.
+        let bytes = [13, 1, 4, 0, 0, 0, 1, 2, 5, 0, 0, 0, 1, 0xFF];
+        let err = ProfileV1::from_bytes(&bytes[..]).unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError::InvalidUtf8 {
+                field: Some("name"),
+                offset: 13
+            }
+        );
+    }
+
+    #[test]
+    fn test_truncated_body_is_eof() {
+        let bytes = v2().to_bytes().unwrap();
+        let err = ProfileV2::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_messages_nest() {
+        crate::tlv_struct! {
+            #[derive(PartialEq)]
+            struct Envelope {
+                #[tlv(1)]
+                profile: ProfileV1,
+                #[tlv(2, optional)]
+                sequence: u64,
+            }
+        }
+
+        let envelope = Envelope {
+            profile: ProfileV1 {
+                id: 1,
+                name: String::from("n"),
+            },
+            sequence: 9,
+        };
+        let bytes = envelope.to_bytes().unwrap();
+        assert_eq!(Envelope::from_bytes(&bytes).unwrap(), envelope);
+    }
+}
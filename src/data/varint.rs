@@ -0,0 +1,212 @@
+use crate::data::codec::{Decode, Decoder, Encode, preallocate};
+use crate::data::error::DecodeError;
+use std::io::{self, Write};
+
+/// An unsigned integer encoded as LEB128: seven bits per byte, least significant group
+/// first, with the high bit set on every byte but the last. Values below 128 take one byte.
+///
+/// Decoding rejects values that overflow `T` and encodings padded with redundant zero
+/// groups, so every value has exactly one valid encoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Varint<T>(pub T);
+
+/// A signed integer zigzag-mapped onto an unsigned one (0, -1, 1, -2, ... become
+/// 0, 1, 2, 3, ...) and then encoded as a [`Varint`], so small negative values stay short.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ZigZag<T>(pub T);
+
+/// A string or vector whose length prefix is a [`Varint`] instead of a fixed `u32`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VarLen<T>(pub T);
+
+/// Writes `value` as LEB128.
+pub fn encode_varint<W: Write>(mut value: u128, writer: &mut W) -> io::Result<()> {
+    let mut buf = [0u8; 19];
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf[len] = byte;
+            len += 1;
+            break;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+    writer.write_all(&buf[..len])
+}
+
+/// How many bytes [`encode_varint`] writes for `value`.
+pub fn varint_len(value: u128) -> usize {
+    let bits = 128 - value.leading_zeros() as usize;
+    bits.div_ceil(7).max(1)
+}
+
+/// Reads a LEB128 value that must fit in `bits` bits.
+pub fn decode_varint(decoder: &mut Decoder<'_>, bits: u32) -> Result<u128, DecodeError> {
+    let start = decoder.offset();
+    let mut value = 0u128;
+    let mut shift = 0u32;
+    loop {
+        let byte = u8::decode(decoder)?;
+        let group = u128::from(byte & 0x7f);
+        if shift >= bits || (bits - shift < 7 && group >> (bits - shift) != 0) {
+            return Err(DecodeError::invalid(
+                start,
+                format!("varint overflows {} bits", bits),
+            ));
+        }
+        value |= group << shift;
+        if byte & 0x80 == 0 {
+            if byte == 0 && shift > 0 {
+                return Err(DecodeError::invalid(start, "over-long varint encoding"));
+            }
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+macro_rules! varint_impls {
+    ($($t:ty)*) => {$(
+        impl Encode for Varint<$t> {
+            fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                encode_varint(self.0 as u128, writer)
+            }
+
+            fn encoded_len(&self) -> usize {
+                varint_len(self.0 as u128)
+            }
+        }
+
+        impl<'de> Decode<'de> for Varint<$t> {
+            fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+                let value = decode_varint(decoder, <$t>::BITS)?;
+                Ok(Varint(value as $t))
+            }
+        }
+
+        impl From<$t> for Varint<$t> {
+            fn from(value: $t) -> Self {
+                Varint(value)
+            }
+        }
+    )*};
+}
+
+varint_impls!(u8 u16 u32 u64 u128 usize);
+
+macro_rules! zigzag_impls {
+    ($($t:ty => $unsigned:ty),*) => {$(
+        impl ZigZag<$t> {
+            fn zigzag(self) -> $unsigned {
+                ((self.0 << 1) ^ (self.0 >> (<$t>::BITS - 1))) as $unsigned
+            }
+        }
+
+        impl Encode for ZigZag<$t> {
+            fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                Varint(self.zigzag()).encode(writer)
+            }
+
+            fn encoded_len(&self) -> usize {
+                Varint(self.zigzag()).encoded_len()
+            }
+        }
+
+        impl<'de> Decode<'de> for ZigZag<$t> {
+            fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+                let Varint(value) = Varint::<$unsigned>::decode(decoder)?;
+                Ok(ZigZag(((value >> 1) as $t) ^ -((value & 1) as $t)))
+            }
+        }
+
+        impl From<$t> for ZigZag<$t> {
+            fn from(value: $t) -> Self {
+                ZigZag(value)
+            }
+        }
+    )*};
+}
+
+zigzag_impls!(
+    i8 => u8,
+    i16 => u16,
+    i32 => u32,
+    i64 => u64,
+    i128 => u128,
+    isize => usize
+);
+
+fn decode_string_len(decoder: &mut Decoder<'_>) -> Result<usize, DecodeError> {
+    let offset = decoder.offset();
+    let Varint(len) = Varint::<usize>::decode(decoder)?;
+    decoder.check_string_len(len, offset)?;
+    Ok(len)
+}
+
+fn decode_collection_len(decoder: &mut Decoder<'_>) -> Result<usize, DecodeError> {
+    let offset = decoder.offset();
+    let Varint(len) = Varint::<usize>::decode(decoder)?;
+    decoder.check_collection_len(len, offset)?;
+    Ok(len)
+}
+
+impl Encode for VarLen<&str> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        Varint(self.0.len()).encode(writer)?;
+        writer.write_all(self.0.as_bytes())
+    }
+
+    fn encoded_len(&self) -> usize {
+        Varint(self.0.len()).encoded_len() + self.0.len()
+    }
+}
+
+impl<'de> Decode<'de> for VarLen<&'de str> {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        let len = decode_string_len(decoder)?;
+        decoder.read_str(len).map(VarLen)
+    }
+}
+
+impl Encode for VarLen<String> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        VarLen(self.0.as_str()).encode(writer)
+    }
+
+    fn encoded_len(&self) -> usize {
+        VarLen(self.0.as_str()).encoded_len()
+    }
+}
+
+impl<'de> Decode<'de> for VarLen<String> {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        VarLen::<&str>::decode(decoder).map(|VarLen(s)| VarLen(String::from(s)))
+    }
+}
+
+impl<T: Encode> Encode for VarLen<Vec<T>> {
+    fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        Varint(self.0.len()).encode(writer)?;
+        self.0.iter().try_for_each(|item| item.encode(writer))
+    }
+
+    fn encoded_len(&self) -> usize {
+        Varint(self.0.len()).encoded_len() + self.0.iter().map(Encode::encoded_len).sum::<usize>()
+    }
+}
+
+impl<'de, T: Decode<'de>> Decode<'de> for VarLen<Vec<T>> {
+    fn decode(decoder: &mut Decoder<'de>) -> Result<Self, DecodeError> {
+        let len = decode_collection_len(decoder)?;
+        decoder.nested(|decoder| {
+            let mut items = Vec::with_capacity(preallocate::<T>(len, decoder.remaining()));
+            for _ in 0..len {
+                items.push(T::decode(decoder)?);
+            }
+            Ok(VarLen(items))
+        })
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use crate::data::codec::{Decode, Encode};
+    use crate::data::varint::{VarLen, Varint, ZigZag};
+    use std::io::ErrorKind;
+
+    #[test]
+    fn test_varint_known_encodings() {
+        assert_eq!(Varint(0u32).to_bytes().unwrap(), [0x00]);
+        assert_eq!(Varint(1u32).to_bytes().unwrap(), [0x01]);
+        assert_eq!(Varint(127u32).to_bytes().unwrap(), [0x7f]);
+        assert_eq!(Varint(128u32).to_bytes().unwrap(), [0x80, 0x01]);
+        assert_eq!(Varint(300u32).to_bytes().unwrap(), [0xac, 0x02]);
+        assert_eq!(
+            Varint(u32::MAX).to_bytes().unwrap(),
+            [0xff, 0xff, 0xff, 0xff, 0x0f]
+        );
+    }
+
+    #[test]
+    fn test_varint_round_trips_boundaries() {
+        for value in [0, 1, 127, 128, 16_383, 16_384, u64::MAX >> 1, u64::MAX] {
+            let bytes = Varint(value).to_bytes().unwrap();
+            assert_eq!(bytes.len(), Varint(value).encoded_len());
+            assert_eq!(Varint::<u64>::from_bytes(&bytes).unwrap(), Varint(value));
+        }
+        let bytes = Varint(u128::MAX).to_bytes().unwrap();
+        assert_eq!(bytes.len(), 19);
+        assert_eq!(Varint::<u128>::from_bytes(&bytes).unwrap(), Varint(u128::MAX));
+    }
+
+    #[test]
+    fn test_zigzag_maps_small_negatives_to_small_values() {
+        for (value, encoded) in [(0i32, 0u8), (-1, 1), (1, 2), (-2, 3), (63, 126), (-64, 127)] {
+            assert_eq!(ZigZag(value).to_bytes().unwrap(), [encoded]);
+        }
+        for value in [i64::MIN, -300, 0, 300, i64::MAX] {
+            let bytes = ZigZag(value).to_bytes().unwrap();
+            assert_eq!(bytes.len(), ZigZag(value).encoded_len());
+            assert_eq!(ZigZag::<i64>::from_bytes(&bytes).unwrap(), ZigZag(value));
+        }
+        for value in [i8::MIN, -1, i8::MAX] {
+            let bytes = ZigZag(value).to_bytes().unwrap();
+            assert_eq!(ZigZag::<i8>::from_bytes(&bytes).unwrap(), ZigZag(value));
+        }
+    }
+
+    #[test]
+    fn test_varint_overflow_is_rejected() {
+        // u32::MAX + 1 needs a 33rd bit.
+        let err = Varint::<u32>::from_bytes(&[0x80, 0x80, 0x80, 0x80, 0x10]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("overflows 32 bits"), "{}", err);
+
+        // A sixth byte is never valid for a u32.
+        let err = Varint::<u32>::from_bytes(&[0xff, 0xff, 0xff, 0xff, 0x8f, 0x00]).unwrap_err();
+        assert!(err.to_string().contains("overflows"), "{}", err);
+
+        let err = Varint::<u8>::from_bytes(&[0x80, 0x02]).unwrap_err();
+        assert!(err.to_string().contains("overflows 8 bits"), "{}", err);
+        assert_eq!(Varint::<u8>::from_bytes(&[0xff, 0x01]).unwrap(), Varint(255));
+    }
+
+    #[test]
+    fn test_varint_over_long_encoding_is_rejected() {
+        let err = Varint::<u32>::from_bytes(&[0x81, 0x00]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("over-long"), "{}", err);
+        assert!(Varint::<u32>::from_bytes(&[0x80, 0x80, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_varint_truncated_is_eof() {
+        let err = Varint::<u32>::from_bytes(&[0x80, 0x80]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_var_len_strings_and_vectors() {
+        let short = VarLen(String::from("hi"));
+        assert_eq!(short.to_bytes().unwrap(), [2, b'h', b'i']);
+        assert_eq!(VarLen::<String>::from_bytes(&[2, b'h', b'i']).unwrap(), short);
+
+        let bytes = VarLen(vec![1u16, 2, 3]).to_bytes().unwrap();
+        assert_eq!(bytes, [3, 0, 1, 0, 2, 0, 3]);
+        assert_eq!(
+            VarLen::<Vec<u16>>::from_bytes(&bytes).unwrap(),
+            VarLen(vec![1, 2, 3])
+        );
+
+        let long = VarLen("x".repeat(200));
+        let bytes = long.to_bytes().unwrap();
+        assert_eq!(&bytes[..2], [0xc8, 0x01]);
+        assert_eq!(bytes.len(), long.encoded_len());
+        let VarLen(borrowed) = VarLen::<&str>::from_bytes(&bytes).unwrap();
+        assert_eq!(borrowed, long.0);
+    }
+
+    #[test]
+    fn test_wrappers_select_encoding_per_field() {
+        crate::wire_struct! {
+            #[derive(PartialEq)]
+            struct Compact {
+                id: Varint<u32>,
+                delta: ZigZag<i32>,
+                fixed: u16,
+                name: VarLen<String>,
+            }
+        }
+
+        let value = Compact {
+            id: Varint(5),
+            delta: ZigZag(-3),
+            fixed: 7,
+            name: VarLen(String::from("a")),
+        };
+        let bytes = value.to_bytes().unwrap();
+        assert_eq!(bytes, [5, 5, 0, 7, 1, b'a']);
+        assert_eq!(Compact::from_bytes(&bytes).unwrap(), value);
+    }
+}
@@ -0,0 +1,215 @@
+//! Declarative macros that implement [`Encode`](crate::data::codec::Encode) and
+//! [`Decode`](crate::data::codec::Decode) for new message types.
+
+/// Declares a struct with named fields and derives `Debug`, `Encode` and `Decode` for it.
+/// Fields are encoded in declaration order. A single lifetime parameter is allowed, for
+/// types that borrow from the input they were decoded from.
+///
+/// ```
+/// std_async::wire_struct! {
+///     #[derive(Clone, PartialEq)]
+///     pub struct Point {
+///         pub x: i32,
+///         pub y: i32,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! wire_struct {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident<$lt:lifetime> {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident : $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        $vis struct $name<$lt> {
+            $($(#[$field_meta])* $field_vis $field: $ty),*
+        }
+
+        $crate::wire_struct!(@encode [<$lt>] $name $($field)*);
+
+        impl<$lt> $crate::data::codec::Decode<$lt> for $name<$lt> {
+            fn decode(
+                decoder: &mut $crate::data::codec::Decoder<$lt>,
+            ) -> ::std::result::Result<Self, $crate::data::error::DecodeError> {
+                decoder.nested(|decoder| {
+                    ::std::result::Result::Ok($name {
+                        $($field: decoder.decode().map_err(|err| err.in_field(stringify!($field)))?),*
+                    })
+                })
+            }
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident : $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $ty),*
+        }
+
+        $crate::wire_struct!(@encode [] $name $($field)*);
+
+        impl<'de> $crate::data::codec::Decode<'de> for $name {
+            fn decode(
+                decoder: &mut $crate::data::codec::Decoder<'de>,
+            ) -> ::std::result::Result<Self, $crate::data::error::DecodeError> {
+                decoder.nested(|decoder| {
+                    ::std::result::Result::Ok($name {
+                        $($field: decoder.decode().map_err(|err| err.in_field(stringify!($field)))?),*
+                    })
+                })
+            }
+        }
+    };
+    (@encode [$($generics:tt)*] $name:ident $($field:ident)*) => {
+        impl$($generics)* $crate::data::codec::Encode for $name$($generics)* {
+            fn encode<W: ::std::io::Write>(&self, writer: &mut W) -> ::std::io::Result<()> {
+                $($crate::data::codec::Encode::encode(&self.$field, writer)?;)*
+                ::std::result::Result::Ok(())
+            }
+
+            fn encoded_len(&self) -> usize {
+                0 $(+ $crate::data::codec::Encode::encoded_len(&self.$field))*
+            }
+        }
+    };
+}
+
+/// Declares an enum and derives `Debug`, `Encode` and `Decode` for it. Every variant needs
+/// an explicit `u8` tag, which is written before the variant's fields and doubles as its
+/// discriminant. Unit variants, tuple variants of up to four fields and variants with named
+/// fields are supported. Decoding an unknown tag fails with `DecodeError::UnknownTag`.
+///
+/// ```
+/// std_async::wire_enum! {
+///     pub enum Command {
+///         Ping = 1,
+///         Say(String) = 2,
+///         Move { x: i32, y: i32 } = 3,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! wire_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($body:tt)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        #[repr(u8)]
+        $vis enum $name {
+            $($body)*
+        }
+
+        $crate::wire_enum!(@parse $name [] $($body)*);
+    };
+
+    // Each variant is normalized to `[tag Variant { key: binding: Type, ... }]`, using
+    // numeric keys for tuple fields, so that one rule can generate every match arm.
+    (@parse $name:ident [$($done:tt)*]) => {
+        $crate::wire_enum!(@impl $name $($done)*);
+    };
+    (@parse $name:ident [$($done:tt)*]
+        $(#[$vmeta:meta])* $variant:ident = $tag:literal $(, $($rest:tt)*)?
+    ) => {
+        $crate::wire_enum!(@parse $name [$($done)* [$tag $variant {}]] $($($rest)*)?);
+    };
+    (@parse $name:ident [$($done:tt)*]
+        $(#[$vmeta:meta])* $variant:ident ($t0:ty $(,)?) = $tag:literal $(, $($rest:tt)*)?
+    ) => {
+        $crate::wire_enum!(@parse $name [$($done)* [$tag $variant { 0: f0: $t0 }]] $($($rest)*)?);
+    };
+    (@parse $name:ident [$($done:tt)*]
+        $(#[$vmeta:meta])* $variant:ident ($t0:ty, $t1:ty $(,)?) = $tag:literal
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::wire_enum!(
+            @parse $name [$($done)* [$tag $variant { 0: f0: $t0, 1: f1: $t1 }]] $($($rest)*)?
+        );
+    };
+    (@parse $name:ident [$($done:tt)*]
+        $(#[$vmeta:meta])* $variant:ident ($t0:ty, $t1:ty, $t2:ty $(,)?) = $tag:literal
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::wire_enum!(
+            @parse $name
+            [$($done)* [$tag $variant { 0: f0: $t0, 1: f1: $t1, 2: f2: $t2 }]]
+            $($($rest)*)?
+        );
+    };
+    (@parse $name:ident [$($done:tt)*]
+        $(#[$vmeta:meta])* $variant:ident ($t0:ty, $t1:ty, $t2:ty, $t3:ty $(,)?) = $tag:literal
+        $(, $($rest:tt)*)?
+    ) => {
+        $crate::wire_enum!(
+            @parse $name
+            [$($done)* [$tag $variant { 0: f0: $t0, 1: f1: $t1, 2: f2: $t2, 3: f3: $t3 }]]
+            $($($rest)*)?
+        );
+    };
+    (@parse $name:ident [$($done:tt)*]
+        $(#[$vmeta:meta])* $variant:ident {
+            $($(#[$fmeta:meta])* $field:ident : $ty:ty),* $(,)?
+        } = $tag:literal $(, $($rest:tt)*)?
+    ) => {
+        $crate::wire_enum!(
+            @parse $name [$($done)* [$tag $variant { $($field: $field: $ty),* }]] $($($rest)*)?
+        );
+    };
+
+    (@impl $name:ident $([$tag:literal $variant:ident { $($key:tt : $bind:ident : $ty:ty),* }])*) => {
+        impl $crate::data::codec::Encode for $name {
+            fn encode<W: ::std::io::Write>(&self, writer: &mut W) -> ::std::io::Result<()> {
+                match self {
+                    $($name::$variant { $($key: $bind),* } => {
+                        $crate::data::codec::Encode::encode(&($tag as u8), writer)?;
+                        $($crate::data::codec::Encode::encode($bind, writer)?;)*
+                    })*
+                }
+                ::std::result::Result::Ok(())
+            }
+
+            fn encoded_len(&self) -> usize {
+                match self {
+                    $($name::$variant { $($key: $bind),* } => {
+                        1 $(+ $crate::data::codec::Encode::encoded_len($bind))*
+                    })*
+                }
+            }
+        }
+
+        impl<'de> $crate::data::codec::Decode<'de> for $name {
+            fn decode(
+                decoder: &mut $crate::data::codec::Decoder<'de>,
+            ) -> ::std::result::Result<Self, $crate::data::error::DecodeError> {
+                decoder.nested(|decoder| {
+                    let offset = decoder.offset();
+                    match decoder.decode::<u8>()? {
+                        $($tag => ::std::result::Result::Ok($name::$variant {
+                            $($key: decoder
+                                .decode::<$ty>()
+                                .map_err(|err| err.in_field(stringify!($key)))?),*
+                        }),)*
+                        tag => ::std::result::Result::Err(
+                            $crate::data::error::DecodeError::UnknownTag {
+                                type_name: stringify!($name),
+                                tag: u32::from(tag),
+                                offset,
+                            },
+                        ),
+                    }
+                })
+            }
+        }
+    };
+}
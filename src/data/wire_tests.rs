@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use crate::data::codec::{Decode, Encode};
+    use crate::data::error::DecodeError;
+    use std::io::ErrorKind;
+
+    crate::wire_struct! {
+        #[derive(Clone, PartialEq)]
+        struct Header {
+            id: u32,
+            flags: u8,
+            name: String,
+        }
+    }
+
+    crate::wire_struct! {
+        #[derive(PartialEq)]
+        struct Borrowed<'a> {
+            id: u16,
+            name: &'a str,
+        }
+    }
+
+    crate::wire_enum! {
+        #[derive(Clone, PartialEq)]
+        enum Command {
+            Ping = 1,
+            Say(String) = 2,
+            Move { x: i32, y: i32 } = 3,
+            Pair(u8, u16) = 7,
+        }
+    }
+
+    #[test]
+    fn test_struct_fields_encode_in_declaration_order() {
+        let header = Header {
+            id: 0x0102_0304,
+            flags: 9,
+            name: String::from("ab"),
+        };
+        let bytes = header.to_bytes().unwrap();
+        assert_eq!(bytes, [1, 2, 3, 4, 9, 0, 0, 0, 2, b'a', b'b']);
+        assert_eq!(header.encoded_len(), bytes.len());
+        assert_eq!(Header::from_bytes(&bytes).unwrap(), header);
+    }
+
+    #[test]
+    fn test_struct_debug() {
+        let header = Header {
+            id: 1,
+            flags: 0,
+            name: String::from("x"),
+        };
+        assert_eq!(format!("{:?}", header), r#"Header { id: 1, flags: 0, name: "x" }"#);
+    }
+
+    #[test]
+    fn test_struct_with_lifetime_borrows_input() {
+        let bytes = Borrowed { id: 5, name: "peer" }.to_bytes().unwrap();
+        let decoded = Borrowed::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, Borrowed { id: 5, name: "peer" });
+        assert_eq!(decoded.name.as_ptr(), bytes[6..].as_ptr());
+    }
+
+    #[test]
+    fn test_enum_round_trips_every_variant() {
+        let commands = [
+            Command::Ping,
+            Command::Say(String::from("hi")),
+            Command::Move { x: -1, y: 2 },
+            Command::Pair(3, 4),
+        ];
+        for command in commands {
+            let bytes = command.to_bytes().unwrap();
+            assert_eq!(bytes.len(), command.encoded_len());
+            assert_eq!(Command::from_bytes(&bytes).unwrap(), command);
+        }
+    }
+
+    #[test]
+    fn test_enum_tag_precedes_fields() {
+        assert_eq!(Command::Ping.to_bytes().unwrap(), [1]);
+        assert_eq!(Command::Pair(3, 4).to_bytes().unwrap(), [7, 3, 0, 4]);
+        assert_eq!(
+            Command::Move { x: 1, y: 2 }.to_bytes().unwrap(),
+            [3, 0, 0, 0, 1, 0, 0, 0, 2]
+        );
+    }
+
+    #[test]
+    fn test_enum_unknown_tag_is_rejected() {
+        let err = Command::from_bytes(&[4]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(
+            err,
+            DecodeError::UnknownTag {
+                type_name: "Command",
+                tag: 4,
+                offset: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_enum_debug() {
+        assert_eq!(format!("{:?}", Command::Move { x: 1, y: 2 }), "Move { x: 1, y: 2 }");
+        assert_eq!(format!("{:?}", Command::Say(String::from("a"))), r#"Say("a")"#);
+    }
+}
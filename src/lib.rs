@@ -1,4 +1,5 @@
 pub mod client;
 pub mod data;
 pub mod runtime;
+#[cfg(unix)]
 pub mod server;
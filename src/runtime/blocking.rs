@@ -0,0 +1,251 @@
+use crate::runtime::clock::Clock;
+use crate::runtime::coop;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    io,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        Arc, Condvar, Mutex, OnceLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+    task::{Context, Poll, Waker, ready},
+    thread,
+    time::Duration,
+};
+
+const DEFAULT_MAX_THREADS: usize = 512;
+const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+/// Runs with `Err` instead when no thread could be started to run it.
+type Job = Box<dyn FnOnce(io::Result<()>) + Send + 'static>;
+
+static GLOBAL: OnceLock<BlockingPool> = OnceLock::new();
+
+/// Runs `f` on the global blocking pool and returns a handle that resolves to its result.
+pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    BlockingPool::global().spawn(f)
+}
+
+/// A growable pool of threads for running blocking closures outside of the executor.
+///
+/// Threads are started on demand up to `max_threads` and exit again after sitting
+/// idle for `keep_alive`.
+pub struct BlockingPool {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    state: Mutex<State>,
+    condvar: Condvar,
+    max_threads: AtomicUsize,
+    keep_alive: Duration,
+}
+
+#[derive(Default)]
+struct State {
+    queue: VecDeque<Job>,
+    threads: usize,
+    idle: usize,
+    notified: usize,
+    spawned: usize,
+    shutdown: bool,
+}
+
+impl BlockingPool {
+    pub fn new(max_threads: usize) -> Self {
+        Self::with_keep_alive(max_threads, DEFAULT_KEEP_ALIVE)
+    }
+
+    pub fn with_keep_alive(max_threads: usize, keep_alive: Duration) -> Self {
+        BlockingPool {
+            inner: Arc::new(Inner {
+                state: Mutex::new(State::default()),
+                condvar: Condvar::new(),
+                max_threads: AtomicUsize::new(max_threads.max(1)),
+                keep_alive,
+            }),
+        }
+    }
+
+    pub fn global() -> &'static BlockingPool {
+        GLOBAL.get_or_init(|| BlockingPool::new(DEFAULT_MAX_THREADS))
+    }
+
+    pub fn spawn<F, R>(&self, f: F) -> JoinHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let slot = Arc::new(Mutex::new(Slot {
+            result: None,
+            waker: None,
+        }));
+        let handle = JoinHandle { slot: slot.clone() };
+        // Keeps a simulated clock from skipping ahead while the job runs.
+        let outstanding = Clock::current().outstanding();
+        self.execute(Box::new(move |ready| {
+            let result = ready.and_then(|()| {
+                panic::catch_unwind(AssertUnwindSafe(f))
+                    .map_err(|_| io::Error::other("blocking task panicked"))
+            });
+            let mut slot = slot.lock().unwrap();
+            slot.result = Some(result);
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+            drop(slot);
+            drop(outstanding);
+        }));
+        handle
+    }
+
+    pub fn max_threads(&self) -> usize {
+        self.inner.max_threads.load(Ordering::SeqCst)
+    }
+
+    /// Changes the thread limit. Threads above a lowered limit finish their current job
+    /// and are reaped once they go idle.
+    pub fn set_max_threads(&self, max_threads: usize) {
+        self.inner
+            .max_threads
+            .store(max_threads.max(1), Ordering::SeqCst);
+    }
+
+    pub fn thread_count(&self) -> usize {
+        self.inner.state.lock().unwrap().threads
+    }
+
+    pub fn idle_count(&self) -> usize {
+        let state = self.inner.state.lock().unwrap();
+        state.idle - state.notified
+    }
+
+    /// Stops accepting work. Queued jobs still run before the threads exit.
+    pub fn shutdown(&self) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.shutdown = true;
+        drop(state);
+        self.inner.condvar.notify_all();
+    }
+
+    fn execute(&self, job: Job) {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.shutdown {
+            // No thread is left to pick the job up, so run it on the caller.
+            drop(state);
+            job(Ok(()));
+            return;
+        }
+        state.queue.push_back(job);
+        if state.idle > state.notified {
+            state.notified += 1;
+            drop(state);
+            self.inner.condvar.notify_one();
+        } else if state.threads < self.max_threads() {
+            state.threads += 1;
+            state.spawned += 1;
+            let name = format!("blocking-{}", state.spawned);
+            drop(state);
+            let inner = self.inner.clone();
+            if let Err(e) = thread::Builder::new().name(name).spawn(move || inner.run()) {
+                let mut state = self.inner.state.lock().unwrap();
+                state.threads -= 1;
+                if state.threads > 0 {
+                    // A running thread will get to the job eventually.
+                    return;
+                }
+                let orphaned: Vec<Job> = state.queue.drain(..).collect();
+                drop(state);
+                for job in orphaned {
+                    job(Err(io::Error::new(
+                        e.kind(),
+                        format!("failed to spawn blocking thread: {}", e),
+                    )));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for BlockingPool {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl Inner {
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            while let Some(job) = state.queue.pop_front() {
+                drop(state);
+                job(Ok(()));
+                state = self.state.lock().unwrap();
+            }
+            if state.shutdown || state.threads > self.max_threads.load(Ordering::SeqCst) {
+                break;
+            }
+            state.idle += 1;
+            let mut timed_out = false;
+            loop {
+                let (guard, result) = self.condvar.wait_timeout(state, self.keep_alive).unwrap();
+                state = guard;
+                if state.notified > 0 {
+                    state.notified -= 1;
+                    break;
+                }
+                if state.shutdown || result.timed_out() {
+                    timed_out = true;
+                    break;
+                }
+                // Spurious wakeup, keep waiting.
+            }
+            state.idle -= 1;
+            if timed_out && state.queue.is_empty() {
+                break;
+            }
+        }
+        state.threads -= 1;
+    }
+}
+
+struct Slot<T> {
+    result: Option<io::Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// Resolves to the return value of a closure passed to [`spawn_blocking`], or to an
+/// error if the closure panicked or no thread could be started to run it.
+pub struct JoinHandle<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    pub fn is_finished(&self) -> bool {
+        self.slot.lock().unwrap().result.is_some()
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = io::Result<T>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let coop = ready!(coop::poll_proceed(cx));
+        let mut slot = self.slot.lock().unwrap();
+        match slot.result.take() {
+            Some(result) => {
+                coop.made_progress();
+                Poll::Ready(result)
+            }
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
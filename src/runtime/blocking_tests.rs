@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::blocking::BlockingPool;
+    use crate::runtime::executor::Executor;
+    use crate::runtime::spawn_blocking;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_spawn_blocking_result_reenters_executor() {
+        let mut executor = Executor::new();
+        let rx = executor.spawn(async {
+            let value = spawn_blocking(|| {
+                thread::sleep(Duration::from_millis(10));
+                21
+            })
+            .await
+            .unwrap();
+            value * 2
+        });
+
+        for _ in 0..1000 {
+            executor.poll();
+            if let Ok(result) = rx.try_recv() {
+                assert_eq!(result, 42);
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        panic!("Blocking task did not complete");
+    }
+
+    #[test]
+    fn test_blocking_does_not_stall_other_tasks() {
+        let mut executor = Executor::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter_clone = counter.clone();
+
+        let blocked = executor.spawn(async {
+            spawn_blocking(|| thread::sleep(Duration::from_millis(50)))
+                .await
+                .unwrap();
+        });
+        let quick = executor.spawn(async move {
+            counter_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        for _ in 0..5 {
+            executor.poll();
+        }
+        assert!(quick.try_recv().is_ok());
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        assert!(blocked.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_pool_respects_max_threads() {
+        let pool = BlockingPool::new(2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let mut handles = Vec::new();
+
+        for _ in 0..6 {
+            let running = running.clone();
+            let peak = peak.clone();
+            handles.push(pool.spawn(move || {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(10));
+                running.fetch_sub(1, Ordering::SeqCst);
+            }));
+        }
+
+        assert!(pool.thread_count() <= 2);
+        while handles.iter().any(|handle| !handle.is_finished()) {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn test_pool_grows_on_demand() {
+        let pool = BlockingPool::new(4);
+        let barrier = Arc::new(Barrier::new(5));
+        let mut handles = Vec::new();
+
+        // Each job waits for the others, so this only finishes if four threads exist at once.
+        for _ in 0..4 {
+            let barrier = barrier.clone();
+            handles.push(pool.spawn(move || {
+                barrier.wait();
+            }));
+        }
+        barrier.wait();
+
+        while handles.iter().any(|handle| !handle.is_finished()) {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(pool.thread_count(), 4);
+    }
+
+    #[test]
+    fn test_idle_threads_are_reaped() {
+        let pool = BlockingPool::with_keep_alive(4, Duration::from_millis(20));
+        let handle = pool.spawn(|| 1);
+        while !handle.is_finished() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(pool.thread_count(), 1);
+
+        for _ in 0..200 {
+            if pool.thread_count() == 0 {
+                return;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        panic!("Idle blocking thread was not reaped");
+    }
+
+    #[test]
+    fn test_panicking_closure_returns_error() {
+        let mut executor = Executor::new();
+        let pool = BlockingPool::new(1);
+        let handle = pool.spawn(|| -> u32 { panic!("boom") });
+        let rx = executor.spawn(handle);
+
+        for _ in 0..1000 {
+            executor.poll();
+            if let Ok(result) = rx.try_recv() {
+                assert!(result.is_err());
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        panic!("Panicking blocking task did not complete");
+    }
+}
@@ -0,0 +1,545 @@
+use crate::runtime::coop;
+use crate::runtime::dump::{TaskDump, TaskDumper};
+use crate::runtime::executor::{self, Executor, Hooks, TaskMeta};
+use crate::runtime::timer::TimerDriver;
+use crate::runtime::waker::TaskWaker;
+use crate::runtime::watchdog::Watchdog;
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    future::Future,
+    io,
+    panic::Location,
+    pin::{Pin, pin},
+    sync::{
+        Arc, Mutex, mpsc,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll, Waker},
+    thread::{self, JoinHandle, Thread},
+    time::{Duration, Instant},
+};
+
+const DEFAULT_THREAD_NAME: &str = "runtime-worker";
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+struct Spawned {
+    future: BoxFuture,
+    name: Option<String>,
+    location: &'static Location<'static>,
+}
+
+thread_local! {
+    static DRIVERS: RefCell<Option<Drivers>> = const { RefCell::new(None) };
+}
+
+#[derive(Clone)]
+struct Drivers {
+    timer: Option<TimerDriver>,
+    io: bool,
+}
+
+/// The timer thread of the runtime driving this thread, if it was built with timers.
+pub(crate) fn timer_driver() -> Option<TimerDriver> {
+    DRIVERS.with(|drivers| drivers.borrow().as_ref()?.timer.clone())
+}
+
+/// Fails if the runtime driving this thread was built without I/O. Outside of a
+/// [`Runtime`] I/O is always allowed.
+pub(crate) fn io_enabled() -> io::Result<()> {
+    if DRIVERS.with(|drivers| drivers.borrow().as_ref().is_some_and(|drivers| !drivers.io)) {
+        return Err(io::Error::other(
+            "I/O is disabled on this runtime; call Builder::enable_io",
+        ));
+    }
+    Ok(())
+}
+
+struct DriversGuard(Option<Drivers>);
+
+impl Drop for DriversGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        DRIVERS.with(|drivers| *drivers.borrow_mut() = previous);
+    }
+}
+
+fn enter_drivers(drivers: &Drivers) -> DriversGuard {
+    DriversGuard(DRIVERS.with(|current| current.replace(Some(drivers.clone()))))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    CurrentThread,
+    MultiThread,
+}
+
+/// Configures and creates a [`Runtime`].
+///
+/// A current-thread runtime runs its tasks on whichever thread calls
+/// [`Runtime::block_on`]. A multi-thread runtime starts a fixed set of worker threads,
+/// each with its own [`Executor`], fed from a shared queue.
+pub struct Builder {
+    kind: Kind,
+    worker_threads: Option<usize>,
+    thread_name: String,
+    thread_stack_size: Option<usize>,
+    enable_time: bool,
+    enable_io: bool,
+    queue_capacity: usize,
+    slow_poll_threshold: Option<Duration>,
+    watchdog: bool,
+    hooks: Hooks,
+}
+
+impl Builder {
+    pub fn new_current_thread() -> Builder {
+        Builder::new(Kind::CurrentThread)
+    }
+
+    pub fn new_multi_thread() -> Builder {
+        Builder::new(Kind::MultiThread)
+    }
+
+    fn new(kind: Kind) -> Builder {
+        Builder {
+            kind,
+            worker_threads: None,
+            thread_name: String::from(DEFAULT_THREAD_NAME),
+            thread_stack_size: None,
+            enable_time: false,
+            enable_io: false,
+            queue_capacity: usize::MAX,
+            slow_poll_threshold: None,
+            watchdog: false,
+            hooks: Hooks::default(),
+        }
+    }
+
+    /// Number of worker threads of a multi-thread runtime. Defaults to the available
+    /// parallelism. Panics if `count` is zero.
+    pub fn worker_threads(&mut self, count: usize) -> &mut Self {
+        assert!(count > 0, "worker_threads must be at least 1");
+        self.worker_threads = Some(count);
+        self
+    }
+
+    /// Prefix for worker thread names; workers are called `{name}-0`, `{name}-1`, ...
+    pub fn thread_name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.thread_name = name.into();
+        self
+    }
+
+    pub fn thread_stack_size(&mut self, size: usize) -> &mut Self {
+        self.thread_stack_size = Some(size);
+        self
+    }
+
+    /// Starts a timer thread that wakes [`Sleep`](crate::runtime::sleep::Sleep)s and
+    /// timeouts when they are due, so their tasks are left alone and idle threads park
+    /// until then. Without it they still work, but their tasks are polled over and over
+    /// until the deadline.
+    pub fn enable_time(&mut self) -> &mut Self {
+        self.enable_time = true;
+        self
+    }
+
+    /// Allows the runtime's sockets, pipes and files. Without it their operations fail.
+    pub fn enable_io(&mut self) -> &mut Self {
+        self.enable_io = true;
+        self
+    }
+
+    pub fn enable_all(&mut self) -> &mut Self {
+        self.enable_time().enable_io()
+    }
+
+    /// Most tasks a worker keeps in its own run queue. Tasks beyond that wait in the
+    /// shared queue until a worker has room. Panics if `capacity` is zero.
+    pub fn queue_capacity(&mut self, capacity: usize) -> &mut Self {
+        assert!(capacity > 0, "queue_capacity must be at least 1");
+        self.queue_capacity = capacity;
+        self
+    }
+
+    /// Reports every poll that takes at least `threshold`, which usually means the task
+    /// blocked the thread. Reports go to `on_slow_poll`, or are printed if it is not set.
+    pub fn slow_poll_threshold(&mut self, threshold: Duration) -> &mut Self {
+        self.slow_poll_threshold = Some(threshold);
+        self
+    }
+
+    /// Called after a poll that exceeded the slow poll threshold.
+    pub fn on_slow_poll(&mut self, f: impl Fn(&TaskMeta) + Send + Sync + 'static) -> &mut Self {
+        self.hooks.on_slow_poll = Some(Arc::new(f));
+        self
+    }
+
+    /// Runs a [`Watchdog`] over the runtime's tasks, using the slow poll threshold. `build`
+    /// fails if no threshold is set.
+    pub fn watchdog(&mut self, enabled: bool) -> &mut Self {
+        self.watchdog = enabled;
+        self
+    }
+
+    pub fn on_task_spawn(&mut self, f: impl Fn(&TaskMeta) + Send + Sync + 'static) -> &mut Self {
+        self.hooks.on_task_spawn = Some(Arc::new(f));
+        self
+    }
+
+    pub fn before_poll(&mut self, f: impl Fn(&TaskMeta) + Send + Sync + 'static) -> &mut Self {
+        self.hooks.before_poll = Some(Arc::new(f));
+        self
+    }
+
+    /// Called after every poll, with `last_poll` set to how long it took.
+    pub fn after_poll(&mut self, f: impl Fn(&TaskMeta) + Send + Sync + 'static) -> &mut Self {
+        self.hooks.after_poll = Some(Arc::new(f));
+        self
+    }
+
+    /// Called once a task's future has completed, after its final `after_poll`.
+    pub fn on_task_terminate(
+        &mut self,
+        f: impl Fn(&TaskMeta) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.hooks.on_task_terminate = Some(Arc::new(f));
+        self
+    }
+
+    /// Called when a worker thread runs out of tasks and parks.
+    pub fn on_thread_park(&mut self, f: impl Fn() + Send + Sync + 'static) -> &mut Self {
+        self.hooks.on_thread_park = Some(Arc::new(f));
+        self
+    }
+
+    pub fn on_thread_unpark(&mut self, f: impl Fn() + Send + Sync + 'static) -> &mut Self {
+        self.hooks.on_thread_unpark = Some(Arc::new(f));
+        self
+    }
+
+    pub fn build(&mut self) -> io::Result<Runtime> {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue {
+                tasks: VecDeque::new(),
+                idle: Vec::new(),
+            }),
+            shutdown: AtomicBool::new(false),
+            queue_capacity: self.queue_capacity,
+            drivers: Drivers {
+                timer: match self.enable_time {
+                    true => Some(TimerDriver::spawn(format!("{}-timer", self.thread_name))?),
+                    false => None,
+                },
+                io: self.enable_io,
+            },
+            hooks: self.hooks.clone(),
+            dumper: TaskDumper::new(),
+            slow_poll_threshold: self.slow_poll_threshold,
+        });
+        let watchdog = match (self.watchdog, self.slow_poll_threshold) {
+            (true, Some(threshold)) => Some(Watchdog::spawn(shared.dumper.clone(), threshold)?),
+            (true, None) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the watchdog needs Builder::slow_poll_threshold",
+                ));
+            }
+            (false, _) => None,
+        };
+        let mut workers = Vec::new();
+        if self.kind == Kind::MultiThread {
+            let count = match self.worker_threads {
+                Some(count) => count,
+                None => thread::available_parallelism()?.get(),
+            };
+            for index in 0..count {
+                let mut builder =
+                    thread::Builder::new().name(format!("{}-{}", self.thread_name, index));
+                if let Some(size) = self.thread_stack_size {
+                    builder = builder.stack_size(size);
+                }
+                let shared = shared.clone();
+                workers.push(builder.spawn(move || shared.run_worker())?);
+            }
+        }
+        let local = (self.kind == Kind::CurrentThread).then(|| Mutex::new(shared.executor()));
+        Ok(Runtime {
+            handle: Handle { shared },
+            workers,
+            local,
+            _watchdog: watchdog,
+        })
+    }
+}
+
+struct Queue {
+    tasks: VecDeque<Spawned>,
+    // Workers parked waiting for tasks.
+    idle: Vec<Thread>,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    shutdown: AtomicBool,
+    queue_capacity: usize,
+    drivers: Drivers,
+    hooks: Hooks,
+    // Shared by every executor of the runtime, so task ids are unique runtime-wide.
+    dumper: TaskDumper,
+    slow_poll_threshold: Option<Duration>,
+}
+
+impl Shared {
+    fn executor(&self) -> Executor {
+        Executor::from_config(executor::Config {
+            hooks: self.hooks.clone(),
+            dumper: self.dumper.clone(),
+            slow_poll_threshold: self.slow_poll_threshold,
+            ..executor::Config::default()
+        })
+    }
+
+    // Moves tasks from the shared queue into `executor` until it is at capacity.
+    fn fill(&self, executor: &mut Executor) {
+        let room = self.queue_capacity.saturating_sub(executor.polling.len());
+        if room == 0 {
+            return;
+        }
+        let mut queue = self.queue.lock().unwrap();
+        let count = room.min(queue.tasks.len());
+        for task in queue.tasks.drain(..count) {
+            executor.spawn_at(task.name, task.location, task.future);
+        }
+    }
+
+    fn run_worker(&self) {
+        let _drivers = enter_drivers(&self.drivers);
+        let mut executor = self.executor();
+        while !self.shutdown.load(Ordering::SeqCst) {
+            self.fill(&mut executor);
+            if executor.poll() {
+                continue;
+            }
+            {
+                let mut queue = self.queue.lock().unwrap();
+                if self.shutdown.load(Ordering::SeqCst) {
+                    continue;
+                }
+                // A full worker only waits for its own tasks; new ones go to a worker with room.
+                if executor.polling.len() < self.queue_capacity {
+                    if !queue.tasks.is_empty() {
+                        continue;
+                    }
+                    queue.idle.push(thread::current());
+                }
+            }
+            // Parks until a task is woken or spawned. An unpark that lands before we park
+            // leaves a token, so no wakeup is lost.
+            executor.park();
+            let current = thread::current().id();
+            self.queue.lock().unwrap().idle.retain(|thread| thread.id() != current);
+        }
+    }
+
+    fn begin_shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if let Some(timer) = &self.drivers.timer {
+            timer.shutdown();
+        }
+        let idle = std::mem::take(&mut self.queue.lock().unwrap().idle);
+        for thread in idle {
+            thread.unpark();
+        }
+    }
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        // Also covers a `build` that failed after starting the timer thread.
+        if let Some(timer) = &self.drivers.timer {
+            timer.shutdown();
+        }
+    }
+}
+
+/// A cloneable handle for spawning onto a [`Runtime`] from anywhere, including from its
+/// own tasks and from other threads.
+#[derive(Clone)]
+pub struct Handle {
+    shared: Arc<Shared>,
+}
+
+impl Handle {
+    /// Queues `future` on the runtime. Like [`Executor::spawn`], the result is sent on the
+    /// returned channel once the task completes, and the channel disconnects if it panics.
+    #[track_caller]
+    pub fn spawn<F, T>(&self, future: F) -> mpsc::Receiver<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.spawn_at(None, Location::caller(), future)
+    }
+
+    #[track_caller]
+    pub fn spawn_named<F, T>(&self, name: impl Into<String>, future: F) -> mpsc::Receiver<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.spawn_at(Some(name.into()), Location::caller(), future)
+    }
+
+    /// A snapshot of every live task on the runtime. Tasks still waiting in the shared
+    /// queue for a worker are not included.
+    pub fn dump(&self) -> Vec<TaskDump> {
+        self.shared.dumper.dump()
+    }
+
+    fn spawn_at<F, T>(
+        &self,
+        name: Option<String>,
+        location: &'static Location<'static>,
+        future: F,
+    ) -> mpsc::Receiver<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        let task = Spawned {
+            future: Box::pin(async move {
+                let _ = tx.send(future.await);
+            }),
+            name,
+            location,
+        };
+        let idle = {
+            let mut queue = self.shared.queue.lock().unwrap();
+            queue.tasks.push_back(task);
+            queue.idle.pop()
+        };
+        if let Some(thread) = idle {
+            thread.unpark();
+        }
+        rx
+    }
+}
+
+/// A configured set of executors, created with [`Builder`].
+pub struct Runtime {
+    handle: Handle,
+    workers: Vec<JoinHandle<()>>,
+    // The executor of a current-thread runtime, driven by `block_on`.
+    local: Option<Mutex<Executor>>,
+    _watchdog: Option<Watchdog>,
+}
+
+impl Runtime {
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    #[track_caller]
+    pub fn spawn<F, T>(&self, future: F) -> mpsc::Receiver<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.handle.spawn(future)
+    }
+
+    #[track_caller]
+    pub fn spawn_named<F, T>(&self, name: impl Into<String>, future: F) -> mpsc::Receiver<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.handle.spawn_named(name, future)
+    }
+
+    /// Runs `future` to completion on the calling thread. On a current-thread runtime this
+    /// is also what drives spawned tasks, in between polls of `future`.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let shared = &self.handle.shared;
+        let _drivers = enter_drivers(&shared.drivers);
+        let mut local = self.local.as_ref().map(|local| {
+            local
+                .try_lock()
+                .expect("cannot call block_on from inside block_on")
+        });
+        let state = TaskWaker::unparking(thread::current());
+        let waker = Waker::from(state.clone());
+        let mut future = pin!(future);
+        loop {
+            if state.take_woken() {
+                let context = &mut Context::from_waker(&waker);
+                if let Poll::Ready(output) = coop::budget(|| future.as_mut().poll(context)) {
+                    return output;
+                }
+            }
+            let Some(executor) = local.as_deref_mut() else {
+                // Woken through `waker`, which unparks this thread.
+                if !state.is_woken() {
+                    thread::park();
+                }
+                continue;
+            };
+            shared.fill(executor);
+            if executor.poll() {
+                continue;
+            }
+            {
+                let mut queue = shared.queue.lock().unwrap();
+                if state.is_woken() {
+                    continue;
+                }
+                if executor.polling.len() < shared.queue_capacity {
+                    if !queue.tasks.is_empty() {
+                        continue;
+                    }
+                    // Spawning a task unparks us just like a worker.
+                    queue.idle.push(thread::current());
+                }
+            }
+            executor.park();
+            let current = thread::current().id();
+            shared.queue.lock().unwrap().idle.retain(|thread| thread.id() != current);
+        }
+    }
+
+    fn begin_shutdown(&self) {
+        self.handle.shared.begin_shutdown();
+        // Workers with a full queue park outside the idle list, waiting on their own tasks.
+        for worker in &self.workers {
+            worker.thread().unpark();
+        }
+    }
+
+    /// Stops the workers and waits up to `timeout` for them to exit. Tasks that have not
+    /// finished are dropped. Returns whether every worker exited in time.
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> bool {
+        self.begin_shutdown();
+        let deadline = Instant::now() + timeout;
+        let workers = std::mem::take(&mut self.workers);
+        while workers.iter().any(|worker| !worker.is_finished()) {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        for worker in workers {
+            let _ = worker.join();
+        }
+        true
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        // Workers exit after their current poll; use `shutdown_timeout` to wait for them.
+        self.begin_shutdown();
+    }
+}
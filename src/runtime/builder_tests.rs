@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::sleep::Sleep;
+    use crate::runtime::udp::UdpSocket;
+    use crate::runtime::{Builder, yield_now};
+    use std::future::{self, Future};
+    use std::pin::pin;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, mpsc};
+    use std::task::{Poll, Waker};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_current_thread_block_on_drives_spawned_tasks() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let rx = runtime.spawn(async {
+            yield_now().await;
+            21
+        });
+        let doubled = runtime.block_on(async move {
+            loop {
+                if let Ok(value) = rx.try_recv() {
+                    return value * 2;
+                }
+                yield_now().await;
+            }
+        });
+        assert_eq!(doubled, 42);
+    }
+
+    #[test]
+    fn test_multi_thread_runs_tasks_on_named_workers() {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("pool")
+            .thread_stack_size(256 * 1024)
+            .build()
+            .unwrap();
+        let names: Vec<_> = (0..4)
+            .map(|_| runtime.spawn(async { std::thread::current().name().map(String::from) }))
+            .collect();
+        for rx in names {
+            let name = rx.recv_timeout(Duration::from_secs(5)).unwrap().unwrap();
+            assert!(name == "pool-0" || name == "pool-1", "{}", name);
+        }
+        assert!(runtime.shutdown_timeout(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_handle_spawns_from_inside_a_task() {
+        let runtime = Builder::new_multi_thread().worker_threads(1).build().unwrap();
+        let handle = runtime.handle().clone();
+        let outer = runtime.spawn(async move { handle.spawn(async { "inner" }) });
+        let inner = outer.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(inner.recv_timeout(Duration::from_secs(5)).unwrap(), "inner");
+    }
+
+    #[test]
+    fn test_queue_capacity_limits_tasks_per_worker() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1)
+            .queue_capacity(2)
+            .build()
+            .unwrap();
+        let tasks: Vec<_> = (0..6)
+            .map(|_| {
+                let (running, most) = (running.clone(), most.clone());
+                runtime.spawn(async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    most.fetch_max(now, Ordering::SeqCst);
+                    for _ in 0..5 {
+                        yield_now().await;
+                    }
+                    running.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for rx in tasks {
+            rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+        assert_eq!(most.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_hooks_apply_to_every_worker() {
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let parked = Arc::new(AtomicUsize::new(0));
+        let (on_spawn, on_park) = (spawned.clone(), parked.clone());
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(2)
+            .on_task_spawn(move |_| {
+                on_spawn.fetch_add(1, Ordering::SeqCst);
+            })
+            .on_thread_park(move || {
+                on_park.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .unwrap();
+        let tasks: Vec<_> = (0..8).map(|n| runtime.spawn(async move { n })).collect();
+        for rx in tasks {
+            rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+        assert_eq!(spawned.load(Ordering::SeqCst), 8);
+        assert!(runtime.shutdown_timeout(Duration::from_secs(5)));
+        assert!(parked.load(Ordering::SeqCst) >= 1);
+    }
+
+    // Counts how often the wrapped future is polled.
+    async fn count_polls<F: Future>(polls: &AtomicUsize, future: F) -> F::Output {
+        let mut future = pin!(future);
+        future::poll_fn(|cx| {
+            polls.fetch_add(1, Ordering::SeqCst);
+            future.as_mut().poll(cx)
+        })
+        .await
+    }
+
+    #[test]
+    fn test_enable_time_wakes_sleeps_from_the_timer_thread() {
+        let runtime = Builder::new_current_thread().enable_time().build().unwrap();
+        let polls = AtomicUsize::new(0);
+        let started = Instant::now();
+        runtime.block_on(count_polls(&polls, Sleep::new(Duration::from_millis(20))));
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        // Once to register, once when the timer fires.
+        assert_eq!(polls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_spawned_sleep_is_not_polled_until_the_timer_fires() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap();
+        let counted = polls.clone();
+        let rx = runtime.spawn(async move {
+            count_polls(&counted, Sleep::new(Duration::from_millis(50))).await
+        });
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(polls.load(Ordering::SeqCst), 2);
+        assert!(runtime.shutdown_timeout(Duration::from_secs(5)));
+
+        // The same for a task driven by block_on while its own future sleeps longer.
+        polls.store(0, Ordering::SeqCst);
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let counted = polls.clone();
+        let rx = runtime.spawn(async move {
+            count_polls(&counted, Sleep::new(Duration::from_millis(20))).await
+        });
+        runtime.block_on(Sleep::new(Duration::from_millis(50)));
+        rx.try_recv().unwrap();
+        assert_eq!(polls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_sleep_without_enable_time_still_completes() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        let started = Instant::now();
+        runtime.block_on(Sleep::new(Duration::from_millis(5)));
+        assert!(started.elapsed() >= Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_io_fails_without_enable_io() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        let result = runtime.block_on(async {
+            let socket = UdpSocket::bind("127.0.0.1:0")?;
+            socket.recv(&mut [0; 8]).await
+        });
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("enable_io"), "{}", err);
+    }
+
+    #[test]
+    fn test_shutdown_timeout_drops_unfinished_tasks() {
+        let runtime = Builder::new_multi_thread().worker_threads(1).build().unwrap();
+        let rx = runtime.spawn(std::future::pending::<()>());
+        assert!(runtime.shutdown_timeout(Duration::from_secs(5)));
+        assert!(rx.recv().is_err());
+    }
+
+    #[test]
+    fn test_panicking_task_does_not_take_down_its_worker() {
+        let runtime = Builder::new_multi_thread().worker_threads(1).build().unwrap();
+        let panicked = runtime.spawn(async { panic!("task failed") });
+        assert_eq!(
+            panicked.recv_timeout(Duration::from_secs(5)),
+            Err(mpsc::RecvTimeoutError::Disconnected)
+        );
+        let after = runtime.spawn(async { "still running" });
+        assert_eq!(after.recv_timeout(Duration::from_secs(5)).unwrap(), "still running");
+        assert!(runtime.shutdown_timeout(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_block_on_parks_until_woken() {
+        let parked = Arc::new(AtomicUsize::new(0));
+        let on_park = parked.clone();
+        let runtime = Builder::new_current_thread()
+            .enable_time()
+            .on_thread_park(move || {
+                on_park.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .unwrap();
+        let polls = AtomicUsize::new(0);
+        runtime.block_on(count_polls(&polls, Sleep::new(Duration::from_millis(20))));
+        assert_eq!(polls.load(Ordering::SeqCst), 2);
+        assert!(parked.load(Ordering::SeqCst) >= 1);
+    }
+
+    #[test]
+    fn test_spawn_unparks_block_on() {
+        let runtime = Builder::new_current_thread().build().unwrap();
+        let handle = runtime.handle().clone();
+        let done = Arc::new(AtomicBool::new(false));
+        let waker: Arc<Mutex<Option<Waker>>> = Arc::default();
+        let (task_done, task_waker) = (done.clone(), waker.clone());
+        let spawner = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            // Only the spawned task wakes the future, so block_on has to be parked idle
+            // and get unparked by the spawn.
+            handle.spawn(async move {
+                task_done.store(true, Ordering::SeqCst);
+                if let Some(waker) = task_waker.lock().unwrap().take() {
+                    waker.wake();
+                }
+            })
+        });
+        runtime.block_on(future::poll_fn(|cx| {
+            *waker.lock().unwrap() = Some(cx.waker().clone());
+            match done.load(Ordering::SeqCst) {
+                true => Poll::Ready(()),
+                false => Poll::Pending,
+            }
+        }));
+        spawner.join().unwrap();
+    }
+}
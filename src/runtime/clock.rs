@@ -0,0 +1,247 @@
+use std::{
+    cell::RefCell,
+    sync::{Arc, Mutex},
+    task::Waker,
+    time::{Duration, Instant},
+};
+
+thread_local! {
+    static CURRENT: RefCell<Option<Clock>> = const { RefCell::new(None) };
+}
+
+/// The time source used by [`Sleep`](crate::runtime::sleep::Sleep) and timeouts.
+///
+/// The real clock reads `Instant::now()`. A simulated clock starts paused and only moves
+/// when advanced, either by hand or, with auto-advance on, by the executor jumping to the
+/// next timer once every task is idle and nothing outside the executor, like a blocking
+/// job or a child process, is still due to wake one.
+#[derive(Clone, Default)]
+pub struct Clock {
+    simulated: Option<Arc<Mutex<Simulated>>>,
+}
+
+struct Simulated {
+    // Virtual time at `anchor`; while paused it does not move at all.
+    base: Instant,
+    anchor: Option<Instant>,
+    auto_advance: bool,
+    next_timer_id: u64,
+    timers: Vec<Timer>,
+    // Live `Outstanding` guards.
+    outstanding: usize,
+}
+
+struct Timer {
+    id: u64,
+    when: Instant,
+    waker: Waker,
+}
+
+impl Simulated {
+    fn now(&self) -> Instant {
+        match self.anchor {
+            Some(anchor) => self.base + anchor.elapsed(),
+            None => self.base,
+        }
+    }
+
+    fn fire_due_timers(&mut self) {
+        let now = self.now();
+        let mut index = 0;
+        while index < self.timers.len() {
+            if self.timers[index].when <= now {
+                self.timers.swap_remove(index).waker.wake();
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+impl Clock {
+    pub fn real() -> Clock {
+        Clock { simulated: None }
+    }
+
+    /// A paused virtual clock starting at the current instant, with auto-advance on.
+    pub fn simulated() -> Clock {
+        Clock {
+            simulated: Some(Arc::new(Mutex::new(Simulated {
+                base: Instant::now(),
+                anchor: None,
+                auto_advance: true,
+                next_timer_id: 0,
+                timers: Vec::new(),
+                outstanding: 0,
+            }))),
+        }
+    }
+
+    /// The clock of the executor polling on this thread, or the real clock outside of one.
+    pub fn current() -> Clock {
+        CURRENT.with(|current| current.borrow().clone().unwrap_or_default())
+    }
+
+    /// Makes this the current clock until the guard is dropped.
+    pub fn enter(&self) -> ClockGuard {
+        let previous = CURRENT.with(|current| current.replace(Some(self.clone())));
+        ClockGuard { previous }
+    }
+
+    pub fn is_simulated(&self) -> bool {
+        self.simulated.is_some()
+    }
+
+    pub fn now(&self) -> Instant {
+        match &self.simulated {
+            Some(simulated) => simulated.lock().unwrap().now(),
+            None => Instant::now(),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        match &self.simulated {
+            Some(simulated) => simulated.lock().unwrap().anchor.is_none(),
+            None => false,
+        }
+    }
+
+    /// Freezes virtual time. Panics on the real clock.
+    pub fn pause(&self) {
+        let mut simulated = self.expect_simulated("pause");
+        simulated.base = simulated.now();
+        simulated.anchor = None;
+    }
+
+    /// Lets virtual time follow real time again from where it stands. Panics on the real clock.
+    pub fn resume(&self) {
+        let mut simulated = self.expect_simulated("resume");
+        if simulated.anchor.is_none() {
+            simulated.anchor = Some(Instant::now());
+        }
+    }
+
+    /// Moves virtual time forward and wakes every timer that is now due. Panics on the
+    /// real clock.
+    pub fn advance(&self, duration: Duration) {
+        let mut simulated = self.expect_simulated("advance");
+        simulated.base += duration;
+        simulated.fire_due_timers();
+    }
+
+    pub fn set_auto_advance(&self, auto_advance: bool) {
+        self.expect_simulated("set_auto_advance").auto_advance = auto_advance;
+    }
+
+    pub fn auto_advances(&self) -> bool {
+        match &self.simulated {
+            Some(simulated) => {
+                let simulated = simulated.lock().unwrap();
+                simulated.auto_advance && simulated.anchor.is_none()
+            }
+            None => false,
+        }
+    }
+
+    /// Marks an event outside the executor, such as a blocking job, as pending until the
+    /// guard is dropped. Auto-advance waits for it, since time should not jump past a
+    /// timeout while the work it guards is still running. A no-op on the real clock.
+    pub(crate) fn outstanding(&self) -> Outstanding {
+        if let Some(simulated) = &self.simulated {
+            simulated.lock().unwrap().outstanding += 1;
+        }
+        Outstanding {
+            simulated: self.simulated.clone(),
+        }
+    }
+
+    /// Whether an [`Outstanding`] guard is alive, so an idle executor should wait for it
+    /// rather than advance.
+    pub(crate) fn has_outstanding(&self) -> bool {
+        match &self.simulated {
+            Some(simulated) => simulated.lock().unwrap().outstanding > 0,
+            None => false,
+        }
+    }
+
+    /// The earliest deadline of a pending timer on a simulated clock.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let simulated = self.simulated.as_ref()?.lock().unwrap();
+        simulated.timers.iter().map(|timer| timer.when).min()
+    }
+
+    /// Jumps to the earliest pending timer and fires it. Returns false if there is none.
+    pub fn advance_to_next_timer(&self) -> bool {
+        let mut simulated = match &self.simulated {
+            Some(simulated) => simulated.lock().unwrap(),
+            None => return false,
+        };
+        let next = match simulated.timers.iter().map(|timer| timer.when).min() {
+            Some(next) => next,
+            None => return false,
+        };
+        let now = simulated.now();
+        if next > now {
+            simulated.base += next - now;
+        }
+        simulated.fire_due_timers();
+        true
+    }
+
+    /// Registers or refreshes a timer on a simulated clock, returning its id.
+    pub(crate) fn register_timer(&self, id: Option<u64>, when: Instant, waker: &Waker) -> Option<u64> {
+        let mut simulated = self.simulated.as_ref()?.lock().unwrap();
+        if let Some(id) = id
+            && let Some(timer) = simulated.timers.iter_mut().find(|timer| timer.id == id)
+        {
+            timer.when = when;
+            timer.waker.clone_from(waker);
+            return Some(id);
+        }
+        let id = simulated.next_timer_id;
+        simulated.next_timer_id += 1;
+        simulated.timers.push(Timer {
+            id,
+            when,
+            waker: waker.clone(),
+        });
+        Some(id)
+    }
+
+    pub(crate) fn cancel_timer(&self, id: u64) {
+        if let Some(simulated) = &self.simulated {
+            simulated.lock().unwrap().timers.retain(|timer| timer.id != id);
+        }
+    }
+
+    fn expect_simulated(&self, operation: &str) -> std::sync::MutexGuard<'_, Simulated> {
+        match &self.simulated {
+            Some(simulated) => simulated.lock().unwrap(),
+            None => panic!("Clock::{} requires a simulated clock", operation),
+        }
+    }
+}
+
+/// Returned by [`Clock::outstanding`]. Drop it after waking the task waiting on the event.
+pub(crate) struct Outstanding {
+    simulated: Option<Arc<Mutex<Simulated>>>,
+}
+
+impl Drop for Outstanding {
+    fn drop(&mut self) {
+        if let Some(simulated) = &self.simulated {
+            simulated.lock().unwrap().outstanding -= 1;
+        }
+    }
+}
+
+pub struct ClockGuard {
+    previous: Option<Clock>,
+}
+
+impl Drop for ClockGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
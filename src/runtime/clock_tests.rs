@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::clock::Clock;
+    use crate::runtime::executor::Executor;
+    use crate::runtime::blocking::spawn_blocking;
+    use crate::runtime::sleep::{Sleep, timeout};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_current_clock_is_real_outside_executor() {
+        let clock = Clock::current();
+        assert!(!clock.is_simulated());
+        assert!(!clock.auto_advances());
+    }
+
+    #[test]
+    fn test_simulated_clock_is_paused() {
+        let clock = Clock::simulated();
+        let start = clock.now();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.now(), start);
+        assert!(clock.is_paused());
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(clock.now(), start + Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_resume_follows_real_time() {
+        let clock = Clock::simulated();
+        let start = clock.now();
+        clock.resume();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(clock.now() >= start + Duration::from_millis(5));
+
+        clock.pause();
+        let paused_at = clock.now();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.now(), paused_at);
+    }
+
+    #[test]
+    fn test_auto_advance_completes_long_sleep_instantly() {
+        let mut executor = Executor::with_clock(Clock::simulated());
+        let start = executor.clock().now();
+        let real_start = Instant::now();
+
+        let rx = executor.spawn(async {
+            Sleep::new(Duration::from_secs(3600)).await;
+            "an hour later"
+        });
+
+        for _ in 0..10 {
+            executor.poll();
+            if let Ok(result) = rx.try_recv() {
+                assert_eq!(result, "an hour later");
+                assert_eq!(executor.clock().now(), start + Duration::from_secs(3600));
+                assert!(real_start.elapsed() < Duration::from_secs(1));
+                return;
+            }
+        }
+        panic!("Sleep did not complete under auto-advance");
+    }
+
+    #[test]
+    fn test_auto_advance_orders_timers_deterministically() {
+        let mut executor = Executor::with_clock(Clock::simulated());
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        for (id, millis) in [(0, 50), (1, 30), (2, 20), (3, 40), (4, 10)] {
+            let order = order.clone();
+            executor.spawn(async move {
+                Sleep::new(Duration::from_millis(millis)).await;
+                order.lock().unwrap().push(id);
+            });
+        }
+
+        for _ in 0..100 {
+            executor.poll();
+        }
+        assert_eq!(*order.lock().unwrap(), vec![4, 2, 1, 3, 0]);
+        assert!(executor.polling.is_empty());
+    }
+
+    #[test]
+    fn test_manual_advance_without_auto_advance() {
+        let clock = Clock::simulated();
+        clock.set_auto_advance(false);
+        let mut executor = Executor::with_clock(clock.clone());
+
+        let rx = executor.spawn(async {
+            Sleep::new(Duration::from_millis(100)).await;
+        });
+
+        for _ in 0..10 {
+            executor.poll();
+        }
+        assert!(rx.try_recv().is_err());
+        assert_eq!(
+            clock.next_deadline(),
+            Some(clock.now() + Duration::from_millis(100))
+        );
+
+        clock.advance(Duration::from_millis(99));
+        executor.poll();
+        assert!(rx.try_recv().is_err());
+
+        clock.advance(Duration::from_millis(1));
+        executor.poll();
+        assert!(rx.try_recv().is_ok());
+        assert_eq!(clock.next_deadline(), None);
+    }
+
+    #[test]
+    fn test_busy_task_prevents_auto_advance() {
+        let mut executor = Executor::with_clock(Clock::simulated());
+        let start = executor.clock().now();
+
+        executor.spawn(async {
+            Sleep::new(Duration::from_secs(1)).await;
+        });
+        // Keeps waking itself, so the executor never counts as idle.
+        executor.spawn(std::future::poll_fn(|cx| {
+            cx.waker().wake_by_ref();
+            std::task::Poll::<()>::Pending
+        }));
+
+        for _ in 0..50 {
+            executor.poll();
+        }
+        assert_eq!(executor.clock().now(), start);
+        assert_eq!(executor.polling.len(), 2);
+    }
+
+    #[test]
+    fn test_auto_advance_waits_for_blocking_job() {
+        let mut executor = Executor::with_clock(Clock::simulated());
+        let start = executor.clock().now();
+
+        let rx = executor.spawn(async {
+            let job = spawn_blocking(|| std::thread::sleep(Duration::from_millis(50)));
+            timeout(Duration::from_secs(10), job).await
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            executor.poll();
+            if let Ok(result) = rx.try_recv() {
+                // Time must not jump to the timeout while the job is still running.
+                assert!(result.unwrap().is_ok());
+                assert_eq!(executor.clock().now(), start);
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Blocking job did not complete");
+    }
+
+    #[test]
+    #[should_panic(expected = "requires a simulated clock")]
+    fn test_advance_real_clock_panics() {
+        Clock::real().advance(Duration::from_secs(1));
+    }
+}
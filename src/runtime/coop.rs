@@ -0,0 +1,91 @@
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Number of runtime operations a task may complete in one poll before it is made to yield.
+pub const BUDGET: u32 = 128;
+
+thread_local! {
+    // `None` outside of a task poll, where operations are never throttled.
+    static REMAINING: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
+/// Runs `f` with a fresh budget. The executor wraps every task poll in this.
+pub(crate) fn budget<R>(f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<u32>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            REMAINING.with(|remaining| remaining.set(self.0));
+        }
+    }
+    let _restore = Restore(REMAINING.with(|remaining| remaining.replace(Some(BUDGET))));
+    f()
+}
+
+/// Spends one unit of the current task's budget. Once it has run out this wakes the task
+/// and returns `Pending`, so runtime operations that are always ready still let other
+/// tasks run.
+///
+/// The unit is refunded when the returned guard is dropped, unless the operation calls
+/// [`RestoreOnPending::made_progress`] first. Only completed operations use up the budget.
+pub fn poll_proceed(cx: &mut Context<'_>) -> Poll<RestoreOnPending> {
+    REMAINING.with(|remaining| match remaining.get() {
+        Some(0) => {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+        Some(n) => {
+            remaining.set(Some(n - 1));
+            Poll::Ready(RestoreOnPending(Cell::new(Some(n))))
+        }
+        None => Poll::Ready(RestoreOnPending(Cell::new(None))),
+    })
+}
+
+/// Returned by [`poll_proceed`]. Gives the budget unit back on drop unless the operation
+/// made progress.
+pub struct RestoreOnPending(Cell<Option<u32>>);
+
+impl RestoreOnPending {
+    /// Keeps the unit spent, because the operation completed or consumed data.
+    pub fn made_progress(&self) {
+        self.0.set(None);
+    }
+}
+
+impl Drop for RestoreOnPending {
+    fn drop(&mut self) {
+        if let Some(budget) = self.0.get() {
+            REMAINING.with(|remaining| remaining.set(Some(budget)));
+        }
+    }
+}
+
+/// Whether the current task can still complete operations in this poll.
+pub fn has_budget_remaining() -> bool {
+    REMAINING.with(|remaining| remaining.get() != Some(0))
+}
+
+/// Returns `Pending` once so the executor can poll other tasks first.
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::coop::{self, BUDGET};
+    use crate::runtime::executor::Executor;
+    use crate::runtime::io::{AsyncReadExt, AsyncWriteExt};
+    use crate::runtime::unix::UnixStream;
+    use crate::runtime::yield_now;
+    use std::future;
+    use std::sync::{Arc, Mutex};
+    use std::task::Poll;
+
+    fn events() -> Arc<Mutex<Vec<&'static str>>> {
+        Arc::new(Mutex::new(Vec::new()))
+    }
+
+    #[test]
+    fn test_yield_now_lets_other_tasks_run() {
+        let mut executor = Executor::new();
+        let log = events();
+        let (a, b) = (log.clone(), log.clone());
+        executor.spawn(async move {
+            a.lock().unwrap().push("a1");
+            yield_now().await;
+            a.lock().unwrap().push("a2");
+        });
+        executor.spawn(async move {
+            b.lock().unwrap().push("b");
+        });
+        executor.run();
+        assert_eq!(*log.lock().unwrap(), ["a1", "b", "a2"]);
+    }
+
+    #[test]
+    fn test_exhausted_budget_forces_a_yield() {
+        let mut executor = Executor::new();
+        let log = events();
+        let (a, b) = (log.clone(), log.clone());
+        executor.spawn(async move {
+            for _ in 0..BUDGET * 2 {
+                future::poll_fn(|cx| coop::poll_proceed(cx).map(|coop| coop.made_progress())).await;
+            }
+            a.lock().unwrap().push("busy done");
+        });
+        executor.spawn(async move {
+            b.lock().unwrap().push("other");
+        });
+        executor.run();
+        assert_eq!(*log.lock().unwrap(), ["other", "busy done"]);
+    }
+
+    #[test]
+    fn test_budget_is_unlimited_outside_the_executor() {
+        let waker = std::task::Waker::noop();
+        let mut cx = std::task::Context::from_waker(waker);
+        for _ in 0..BUDGET * 2 {
+            assert!(coop::poll_proceed(&mut cx).is_ready());
+        }
+        assert!(coop::has_budget_remaining());
+    }
+
+    #[test]
+    fn test_pending_operations_refund_their_budget() {
+        let mut executor = Executor::new();
+        let rx = executor.spawn(future::poll_fn(|cx| {
+            for _ in 0..BUDGET * 2 {
+                // An operation that would block, and so drops its guard without progress.
+                let _coop = coop::poll_proceed(cx);
+            }
+            Poll::Ready(coop::has_budget_remaining())
+        }));
+        executor.run();
+        assert!(rx.recv().unwrap());
+    }
+
+    #[test]
+    fn test_always_ready_reads_yield_to_other_tasks() {
+        let (mut reader, mut writer) = UnixStream::pair().unwrap();
+        let mut executor = Executor::new();
+        let log = events();
+        let (a, b) = (log.clone(), log.clone());
+        executor.spawn(async move {
+            writer.write_all(&[7; 512]).await.unwrap();
+            writer.shutdown().await.unwrap();
+        });
+        executor.run();
+        executor.spawn(async move {
+            // Every read is ready, so only the budget can interrupt this loop.
+            let mut byte = [0u8; 1];
+            while reader.read(&mut byte).await.unwrap() == 1 {}
+            a.lock().unwrap().push("reader done");
+        });
+        executor.spawn(async move {
+            b.lock().unwrap().push("other");
+        });
+        executor.run();
+        assert_eq!(*log.lock().unwrap(), ["other", "reader done"]);
+    }
+}
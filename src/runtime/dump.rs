@@ -0,0 +1,165 @@
+use crate::runtime::executor::{TaskId, TaskMeta};
+use crate::runtime::waker::TaskWaker;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    panic::Location,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskState {
+    /// Waiting to be woken.
+    Idle,
+    /// Woken and waiting for its next poll.
+    Scheduled,
+    /// Inside `poll` right now.
+    Running,
+}
+
+/// A snapshot of one live task, as returned by [`TaskDumper::dump`].
+#[derive(Clone, Debug)]
+pub struct TaskDump {
+    pub id: TaskId,
+    pub name: Option<String>,
+    pub location: &'static Location<'static>,
+    pub state: TaskState,
+    pub polls: u64,
+    /// Time since the last poll started, so for a running task how long it has been in
+    /// `poll`. `None` if the task has never been polled.
+    pub since_last_poll: Option<Duration>,
+}
+
+impl fmt::Display for TaskDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task {}", self.id)?;
+        if let Some(name) = &self.name {
+            write!(f, " {:?}", name)?;
+        }
+        write!(f, " spawned at {}: {:?}, {} polls", self.location, self.state, self.polls)?;
+        match self.since_last_poll {
+            Some(since) => write!(f, ", last polled {:?} ago", since),
+            None => write!(f, ", never polled"),
+        }
+    }
+}
+
+/// The poll state of one task. The executor updates it around every poll without going
+/// through the registry, which is only locked to add and remove tasks and to take a dump.
+pub(crate) struct TaskStats {
+    spawned_at: Instant,
+    running: AtomicBool,
+    polls: AtomicU64,
+    /// Nanoseconds from `spawned_at` to the start of the last poll, plus one. Zero until
+    /// the first poll.
+    last_polled: AtomicU64,
+}
+
+impl TaskStats {
+    pub(crate) fn start_poll(&self) {
+        let since_spawn = self.spawned_at.elapsed().as_nanos() as u64;
+        self.last_polled.store(since_spawn + 1, Ordering::Relaxed);
+        self.running.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn end_poll(&self, polls: u64) {
+        self.polls.store(polls, Ordering::Relaxed);
+        self.running.store(false, Ordering::Release);
+    }
+
+    fn last_polled(&self) -> Option<Instant> {
+        match self.last_polled.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(self.spawned_at + Duration::from_nanos(nanos - 1)),
+        }
+    }
+}
+
+struct Tracked {
+    name: Option<String>,
+    location: &'static Location<'static>,
+    stats: Arc<TaskStats>,
+    waker: Arc<TaskWaker>,
+}
+
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    tasks: BTreeMap<TaskId, Tracked>,
+}
+
+/// Tracks the live tasks of one or more executors and hands out their ids. Clones share
+/// the same registry and can take snapshots from any thread, including while the
+/// executor is stuck inside a poll.
+#[derive(Clone, Default)]
+pub struct TaskDumper {
+    registry: Arc<Mutex<Registry>>,
+}
+
+impl TaskDumper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every live task, in id order.
+    pub fn dump(&self) -> Vec<TaskDump> {
+        let now = Instant::now();
+        let registry = self.registry.lock().unwrap();
+        registry
+            .tasks
+            .iter()
+            .map(|(&id, task)| TaskDump {
+                id,
+                name: task.name.clone(),
+                location: task.location,
+                state: if task.stats.running.load(Ordering::Acquire) {
+                    TaskState::Running
+                } else if task.waker.is_woken() {
+                    TaskState::Scheduled
+                } else {
+                    TaskState::Idle
+                },
+                polls: task.stats.polls.load(Ordering::Relaxed),
+                since_last_poll: task
+                    .stats
+                    .last_polled()
+                    .map(|at| now.saturating_duration_since(at)),
+            })
+            .collect()
+    }
+
+    pub(crate) fn next_id(&self) -> TaskId {
+        let mut registry = self.registry.lock().unwrap();
+        let id = TaskId(registry.next_id);
+        registry.next_id += 1;
+        id
+    }
+
+    /// Adds a task, returning the stats the executor should update as it polls it.
+    pub(crate) fn insert(&self, meta: &TaskMeta, waker: Arc<TaskWaker>) -> Arc<TaskStats> {
+        let stats = Arc::new(TaskStats {
+            spawned_at: meta.spawned_at,
+            running: AtomicBool::new(false),
+            polls: AtomicU64::new(0),
+            last_polled: AtomicU64::new(0),
+        });
+        self.registry.lock().unwrap().tasks.insert(
+            meta.id,
+            Tracked {
+                name: meta.name.clone(),
+                location: meta.location,
+                stats: stats.clone(),
+                waker,
+            },
+        );
+        stats
+    }
+
+    pub(crate) fn remove(&self, id: TaskId) {
+        self.registry.lock().unwrap().tasks.remove(&id);
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::Builder;
+    use crate::runtime::dump::TaskState;
+    use crate::runtime::executor::{Executor, TaskId};
+    use crate::runtime::yield_now;
+    use std::time::Duration;
+
+    #[test]
+    fn test_dump_reports_name_location_and_state() {
+        let mut executor = Executor::new();
+        let line = line!() + 1;
+        executor.spawn_named("stuck", std::future::pending::<()>());
+        executor.spawn(async {
+            loop {
+                yield_now().await;
+            }
+        });
+        assert!(executor.dump().iter().all(|task| task.polls == 0));
+
+        executor.poll();
+        executor.poll();
+        let dump = executor.dump();
+        assert_eq!(dump.len(), 2);
+
+        assert_eq!(dump[0].id, TaskId(0));
+        assert_eq!(dump[0].name.as_deref(), Some("stuck"));
+        assert_eq!(dump[0].location.file(), file!());
+        assert_eq!(dump[0].location.line(), line);
+        assert_eq!(dump[0].state, TaskState::Idle);
+        assert_eq!(dump[0].polls, 1);
+        assert!(dump[0].since_last_poll.is_some());
+
+        assert_eq!(dump[1].name, None);
+        assert_eq!(dump[1].state, TaskState::Scheduled);
+    }
+
+    #[test]
+    fn test_running_task_is_visible_from_inside_its_poll() {
+        let mut executor = Executor::new();
+        let dumper = executor.dumper();
+        let rx = executor.spawn(async move { dumper.dump() });
+        executor.run();
+        let dump = rx.recv().unwrap();
+        assert_eq!(dump.len(), 1);
+        assert_eq!(dump[0].state, TaskState::Running);
+        assert_eq!(dump[0].polls, 0);
+    }
+
+    #[test]
+    fn test_finished_tasks_leave_the_dump() {
+        let mut executor = Executor::new();
+        executor.spawn(async {});
+        assert_eq!(executor.dump().len(), 1);
+        executor.run();
+        assert!(executor.dump().is_empty());
+    }
+
+    #[test]
+    fn test_runtime_dump_covers_every_worker() {
+        let runtime = Builder::new_multi_thread().worker_threads(2).build().unwrap();
+        for n in 0..4 {
+            runtime.spawn_named(format!("task {}", n), std::future::pending::<()>());
+        }
+        let mut dump = Vec::new();
+        for _ in 0..500 {
+            dump = runtime.handle().dump();
+            if dump.len() == 4 && dump.iter().all(|task| task.polls > 0) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        let ids: Vec<_> = dump.iter().map(|task| task.id).collect();
+        assert_eq!(ids, [TaskId(0), TaskId(1), TaskId(2), TaskId(3)]);
+        let names: Vec<_> = dump.iter().filter_map(|task| task.name.clone()).collect();
+        assert_eq!(names, ["task 0", "task 1", "task 2", "task 3"]);
+        assert!(runtime.shutdown_timeout(Duration::from_secs(5)));
+    }
+}
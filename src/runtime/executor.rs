@@ -1,27 +1,225 @@
-use crate::runtime::waker::create_raw_waker;
+use crate::runtime::clock::Clock;
+use crate::runtime::coop;
+use crate::runtime::dump::{TaskDump, TaskDumper, TaskStats};
+use crate::runtime::schedule::{Rng, Scheduler, panic_message};
+use crate::runtime::waker::{ParkedThread, TaskWaker, create_raw_waker};
 use std::{
     collections::VecDeque,
+    fmt,
     future::Future,
+    panic::{self, AssertUnwindSafe, Location},
     pin::Pin,
     sync::{Arc, mpsc},
     task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
 };
 
+/// Identifies a task within its executor. Ids are handed out in spawn order, so the same
+/// sequence of spawns produces the same ids on every run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TaskId(pub u64);
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// What the executor knows about a task, as passed to the lifecycle hooks. Timings are
+/// wall-clock, even on an executor with a simulated clock.
+#[derive(Clone, Debug)]
+pub struct TaskMeta {
+    pub id: TaskId,
+    pub name: Option<String>,
+    /// Where the task was spawned.
+    pub location: &'static Location<'static>,
+    pub spawned_at: Instant,
+    /// Number of completed polls.
+    pub polls: u64,
+    /// Total time spent inside `poll`.
+    pub busy: Duration,
+    /// How long the most recent poll took; `None` before the first poll has finished.
+    pub last_poll: Option<Duration>,
+}
+
+pub(crate) type TaskHook = Arc<dyn Fn(&TaskMeta) + Send + Sync>;
+pub(crate) type ThreadHook = Arc<dyn Fn() + Send + Sync>;
+
+/// Callbacks installed with [`Builder`](crate::runtime::Builder). Cloning shares the
+/// callbacks.
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    pub(crate) on_task_spawn: Option<TaskHook>,
+    pub(crate) before_poll: Option<TaskHook>,
+    pub(crate) after_poll: Option<TaskHook>,
+    pub(crate) on_task_terminate: Option<TaskHook>,
+    pub(crate) on_slow_poll: Option<TaskHook>,
+    pub(crate) on_thread_park: Option<ThreadHook>,
+    pub(crate) on_thread_unpark: Option<ThreadHook>,
+}
+
+impl Hooks {
+    fn task(hook: &Option<TaskHook>, meta: &TaskMeta) {
+        if let Some(hook) = hook {
+            hook(meta);
+        }
+    }
+
+    fn slow_poll(&self, meta: &TaskMeta) {
+        match &self.on_slow_poll {
+            Some(hook) => hook(meta),
+            None => {
+                let name = meta.name.as_deref().unwrap_or("unnamed");
+                println!(
+                    "Slow poll: task {} ({}) spawned at {} took {:?}",
+                    meta.id,
+                    name,
+                    meta.location,
+                    meta.last_poll.unwrap_or_default()
+                );
+            }
+        }
+    }
+
+    fn thread(hook: &Option<ThreadHook>) {
+        if let Some(hook) = hook {
+            hook();
+        }
+    }
+}
+
 pub struct Task {
+    meta: TaskMeta,
     future: Pin<Box<dyn Future<Output = ()> + Send>>,
-    waker: Arc<Waker>,
+    waker: Waker,
+    state: Arc<TaskWaker>,
+    stats: Arc<TaskStats>,
+}
+
+impl Task {
+    pub fn id(&self) -> TaskId {
+        self.meta.id
+    }
+
+    pub fn meta(&self) -> &TaskMeta {
+        &self.meta
+    }
+
+    /// Whether the task has been woken since it was last polled.
+    pub fn is_woken(&self) -> bool {
+        self.state.is_woken()
+    }
+}
+
+/// What an [`Executor`] is created with. Runtimes fill in the hooks, dumper and slow poll
+/// threshold from their [`Builder`](crate::runtime::Builder).
+#[derive(Default)]
+pub(crate) struct Config {
+    pub(crate) clock: Clock,
+    pub(crate) scheduler: Option<Scheduler>,
+    pub(crate) hooks: Hooks,
+    /// Executors sharing a dumper also share one sequence of task ids.
+    pub(crate) dumper: TaskDumper,
+    pub(crate) slow_poll_threshold: Option<Duration>,
 }
 
 pub struct Executor {
     pub polling: VecDeque<Task>,
+    clock: Clock,
+    scheduler: Scheduler,
+    schedule: Vec<TaskId>,
+    hooks: Hooks,
+    dumper: TaskDumper,
+    slow_poll_threshold: Option<Duration>,
+    panicked: Option<(TaskId, String)>,
+    // Set while `park` is waiting, so that waking a task unparks it.
+    parked: ParkedThread,
+}
+impl Default for Executor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 impl Executor {
     pub fn new() -> Self {
+        Executor::from_config(Config::default())
+    }
+    pub(crate) fn from_config(config: Config) -> Self {
         Executor {
             polling: VecDeque::new(),
+            clock: config.clock,
+            scheduler: config.scheduler.unwrap_or(Scheduler::Fifo),
+            schedule: Vec::new(),
+            hooks: config.hooks,
+            dumper: config.dumper,
+            slow_poll_threshold: config.slow_poll_threshold,
+            panicked: None,
+            parked: ParkedThread::default(),
         }
     }
+    /// Creates an executor whose tasks read time from `clock`. With a simulated clock that
+    /// auto-advances, time jumps to the next timer whenever every task is idle and no
+    /// blocking job or child process is outstanding.
+    pub fn with_clock(clock: Clock) -> Self {
+        Executor::from_config(Config {
+            clock,
+            ..Config::default()
+        })
+    }
+    /// Creates an executor that picks the next task pseudo-randomly from `seed` instead of
+    /// in FIFO order. The same seed and the same spawns give the same interleaving.
+    pub fn seeded(seed: u64) -> Self {
+        Executor::from_config(Config {
+            scheduler: Some(Scheduler::Seeded(Rng::new(seed))),
+            ..Config::default()
+        })
+    }
+    /// Creates an executor that polls tasks in exactly the order recorded in `schedule`,
+    /// as returned by [`Executor::schedule`] on an earlier run.
+    pub fn replay(schedule: Vec<TaskId>) -> Self {
+        Executor::from_config(Config {
+            scheduler: Some(Scheduler::Replay { schedule, step: 0 }),
+            ..Config::default()
+        })
+    }
+    /// The ids of the tasks polled so far, in order. Only recorded by seeded and replaying
+    /// executors.
+    pub fn schedule(&self) -> &[TaskId] {
+        &self.schedule
+    }
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+    /// The first task that panicked since the last call, with its panic message.
+    pub fn take_panic(&mut self) -> Option<(TaskId, String)> {
+        self.panicked.take()
+    }
+    /// Queues `future` and returns a channel its result is sent on. If the task panics or
+    /// is dropped unfinished, the channel disconnects instead.
+    #[track_caller]
     pub fn spawn<F, T>(&mut self, future: F) -> mpsc::Receiver<T>
+    where
+        F: Future<Output = T> + 'static + Send,
+        T: Send + 'static,
+    {
+        self.spawn_at(None, Location::caller(), future)
+    }
+    /// Like [`Executor::spawn`], naming the task in task dumps and hook metadata.
+    #[track_caller]
+    pub fn spawn_named<F, T>(&mut self, name: impl Into<String>, future: F) -> mpsc::Receiver<T>
+    where
+        F: Future<Output = T> + 'static + Send,
+        T: Send + 'static,
+    {
+        self.spawn_at(Some(name.into()), Location::caller(), future)
+    }
+    pub(crate) fn spawn_at<F, T>(
+        &mut self,
+        name: Option<String>,
+        location: &'static Location<'static>,
+        future: F,
+    ) -> mpsc::Receiver<T>
     where
         F: Future<Output = T> + 'static + Send,
         T: Send + 'static,
@@ -31,30 +229,140 @@ impl Executor {
             let result = future.await;
             let _ = tx.send(result);
         });
+        let state = TaskWaker::parking(self.parked.clone());
+        let meta = TaskMeta {
+            id: self.dumper.next_id(),
+            name,
+            location,
+            spawned_at: Instant::now(),
+            polls: 0,
+            busy: Duration::ZERO,
+            last_poll: None,
+        };
+        let stats = self.dumper.insert(&meta, state.clone());
         let task = Task {
+            meta,
             future,
-            waker: self.create_waker(),
+            waker: Waker::from(state.clone()),
+            state,
+            stats,
         };
+        Hooks::task(&self.hooks.on_task_spawn, &task.meta);
         self.polling.push_back(task);
         rx
     }
 
-    pub fn poll(&mut self) {
-        let mut task = match self.polling.pop_front() {
-            Some(task) => task,
-            None => return,
+    /// Polls the next task that has been woken since its last poll. Returns false, having
+    /// polled nothing, if no task is woken.
+    pub fn poll(&mut self) -> bool {
+        let Some(index) = self.scheduler.next_index(&self.polling) else {
+            self.advance_if_idle();
+            return false;
+        };
+        let Some(mut task) = self.polling.remove(index) else {
+            return false;
+        };
+        if self.scheduler.records() {
+            self.schedule.push(task.meta.id);
+        }
+        let _clock = self.clock.is_simulated().then(|| self.clock.enter());
+        task.state.take_woken();
+        let context = &mut Context::from_waker(&task.waker);
+        Hooks::task(&self.hooks.before_poll, &task.meta);
+        task.stats.start_poll();
+        let started = Instant::now();
+        // A panicking task ends on its own, rather than taking the thread and every other
+        // task on it down with it.
+        let poll = match panic::catch_unwind(AssertUnwindSafe(|| {
+            coop::budget(|| task.future.as_mut().poll(context))
+        })) {
+            Ok(poll) => poll,
+            Err(payload) => {
+                self.panicked
+                    .get_or_insert_with(|| (task.meta.id, panic_message(&*payload)));
+                Poll::Ready(())
+            }
         };
-        let waker = task.waker.clone();
-        let context = &mut Context::from_waker(&waker);
-        match task.future.as_mut().poll(context) {
-            Poll::Ready(()) => {}
+        let elapsed = started.elapsed();
+        task.meta.polls += 1;
+        task.meta.busy += elapsed;
+        task.meta.last_poll = Some(elapsed);
+        task.stats.end_poll(task.meta.polls);
+        Hooks::task(&self.hooks.after_poll, &task.meta);
+        if self
+            .slow_poll_threshold
+            .is_some_and(|threshold| elapsed >= threshold)
+        {
+            self.hooks.slow_poll(&task.meta);
+        }
+        match poll {
+            Poll::Ready(()) => {
+                self.dumper.remove(task.meta.id);
+                Hooks::task(&self.hooks.on_task_terminate, &task.meta);
+            }
             Poll::Pending => {
                 self.polling.push_back(task);
             }
         }
+        self.advance_if_idle();
+        true
+    }
+
+    fn advance_if_idle(&self) {
+        if self.clock.auto_advances() && !self.clock.has_outstanding() && !self.has_woken() {
+            self.clock.advance_to_next_timer();
+        }
+    }
+
+    /// Whether any task has been woken since its last poll.
+    pub fn has_woken(&self) -> bool {
+        self.polling.iter().any(Task::is_woken)
+    }
+
+    /// Polls until every spawned task has finished, parking while none is woken.
+    pub fn run(&mut self) {
+        while !self.polling.is_empty() {
+            if !self.poll() {
+                self.park();
+            }
+        }
+    }
+
+    /// A snapshot of every live task on this executor, or on every executor sharing its
+    /// [`TaskDumper`].
+    pub fn dump(&self) -> Vec<TaskDump> {
+        self.dumper.dump()
+    }
+
+    /// A handle for taking task dumps from another thread, e.g. while this one is stuck.
+    pub fn dumper(&self) -> TaskDumper {
+        self.dumper.clone()
+    }
+
+    /// Parks the current thread until it is unparked, which waking any task of this
+    /// executor does, running the park and unpark hooks around it. Returns at once if a
+    /// task is already woken.
+    pub fn park(&self) {
+        self.parked.set(Some(thread::current()));
+        // A task woken before the thread was recorded did not unpark it.
+        if !self.has_woken() {
+            Hooks::thread(&self.hooks.on_thread_park);
+            thread::park();
+            Hooks::thread(&self.hooks.on_thread_unpark);
+        }
+        self.parked.set(None);
     }
 
     pub fn create_waker(&self) -> Arc<Waker> {
         Arc::new(unsafe { Waker::from_raw(create_raw_waker()) })
     }
 }
+
+impl Drop for Executor {
+    fn drop(&mut self) {
+        // The dumper may be shared with other executors, so only forget our own tasks.
+        for task in &self.polling {
+            self.dumper.remove(task.meta.id);
+        }
+    }
+}
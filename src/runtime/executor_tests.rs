@@ -1,5 +1,6 @@
 #[cfg(test)]
 mod tests {
+    use crate::runtime::Builder;
     use crate::runtime::executor::Executor;
     use crate::runtime::sleep::Sleep;
     use std::sync::atomic::{AtomicU32, Ordering};
@@ -74,28 +75,27 @@ mod tests {
 
     #[test]
     fn test_executor_with_sleep_future() {
+        use crate::runtime::clock::Clock;
         use crate::runtime::sleep::Sleep;
-        
-        let mut executor = Executor::new();
-        let start = std::time::Instant::now();
-        
+
+        let mut executor = Executor::with_clock(Clock::simulated());
+        let start = executor.clock().now();
+
         let rx = executor.spawn(async {
             Sleep::new(Duration::from_millis(50)).await;
             "completed"
         });
 
         // Poll until completion
-        for _ in 0..1000 {
+        for _ in 0..10 {
             executor.poll();
             if let Ok(result) = rx.try_recv() {
-                let elapsed = start.elapsed();
                 assert_eq!(result, "completed");
-                assert!(elapsed >= Duration::from_millis(40)); // Some tolerance
+                assert_eq!(executor.clock().now(), start + Duration::from_millis(50));
                 return;
             }
-            std::thread::sleep(Duration::from_millis(1));
         }
-        
+
         panic!("Sleep future did not complete");
     }
 
@@ -141,4 +141,101 @@ mod tests {
         
         panic!("Future should have completed");
     }
+
+    #[test]
+    fn test_lifecycle_hooks_see_every_task_event() {
+        use crate::runtime::executor::TaskId;
+        use crate::runtime::yield_now;
+        use std::sync::Mutex;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let record = |kind: &'static str| {
+            let events = events.clone();
+            move |meta: &crate::runtime::executor::TaskMeta| {
+                events.lock().unwrap().push((kind, meta.id, meta.polls));
+            }
+        };
+        let runtime = Builder::new_current_thread()
+            .on_task_spawn(record("spawn"))
+            .before_poll(record("before"))
+            .after_poll(record("after"))
+            .on_task_terminate(record("terminate"))
+            .build()
+            .unwrap();
+        let rx = runtime.spawn(async { yield_now().await });
+        runtime.block_on(async move {
+            while rx.try_recv().is_err() {
+                yield_now().await;
+            }
+        });
+
+        let id = TaskId(0);
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                ("spawn", id, 0),
+                ("before", id, 0),
+                ("after", id, 1),
+                ("before", id, 1),
+                ("after", id, 2),
+                ("terminate", id, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_after_poll_reports_poll_duration() {
+        let busy = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = busy.clone();
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1)
+            .after_poll(move |meta| seen.lock().unwrap().push((meta.last_poll, meta.busy)))
+            .build()
+            .unwrap();
+        let rx = runtime.spawn(async { std::thread::sleep(Duration::from_millis(5)) });
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(runtime.shutdown_timeout(Duration::from_secs(5)));
+
+        let busy = busy.lock().unwrap();
+        assert_eq!(busy.len(), 1);
+        let (last_poll, total) = busy[0];
+        assert!(last_poll.unwrap() >= Duration::from_millis(5));
+        assert_eq!(last_poll, Some(total));
+    }
+
+    #[test]
+    fn test_park_runs_thread_hooks() {
+        let parks = Arc::new(AtomicU32::new(0));
+        let unparks = Arc::new(AtomicU32::new(0));
+        let (on_park, on_unpark) = (parks.clone(), unparks.clone());
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1)
+            .on_thread_park(move || {
+                on_park.fetch_add(1, Ordering::SeqCst);
+            })
+            .on_thread_unpark(move || {
+                on_unpark.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .unwrap();
+        // The worker parks as soon as it finds nothing to do.
+        while parks.load(Ordering::SeqCst) == 0 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let rx = runtime.spawn(async {});
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(unparks.load(Ordering::SeqCst) >= 1);
+        assert!(runtime.shutdown_timeout(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_panicking_task_is_dropped_alone() {
+        let mut executor = Executor::new();
+        let panicked = executor.spawn(async { panic!("task failed") });
+        let other = executor.spawn(async { 5 });
+        executor.run();
+        assert_eq!(panicked.try_recv(), Err(std::sync::mpsc::TryRecvError::Disconnected));
+        assert_eq!(other.try_recv(), Ok(5));
+        assert!(executor.dump().is_empty());
+    }
 }
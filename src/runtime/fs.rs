@@ -0,0 +1,261 @@
+use crate::runtime::blocking::{JoinHandle, spawn_blocking};
+use crate::runtime::builder;
+use crate::runtime::io::{AsyncRead, AsyncWrite};
+use std::{
+    fs::{self, Metadata},
+    future::{self, Future},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+};
+
+// Upper bound on how much a single read or write hands to the blocking pool.
+const MAX_BUF: usize = 64 * 1024;
+
+async fn asyncify<F, T>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    builder::io_enabled()?;
+    spawn_blocking(f).await?
+}
+
+pub async fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || fs::read(path)).await
+}
+
+pub async fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || fs::read_to_string(path)).await
+}
+
+pub async fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref().to_owned();
+    let contents = contents.as_ref().to_owned();
+    asyncify(move || fs::write(path, contents)).await
+}
+
+pub async fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || fs::create_dir_all(path)).await
+}
+
+pub async fn metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || fs::metadata(path)).await
+}
+
+/// Async counterpart of [`std::fs::OpenOptions`].
+#[derive(Clone, Debug)]
+pub struct OpenOptions(fs::OpenOptions);
+
+impl OpenOptions {
+    pub fn new() -> Self {
+        OpenOptions(fs::OpenOptions::new())
+    }
+
+    pub fn read(&mut self, read: bool) -> &mut Self {
+        self.0.read(read);
+        self
+    }
+
+    pub fn write(&mut self, write: bool) -> &mut Self {
+        self.0.write(write);
+        self
+    }
+
+    pub fn append(&mut self, append: bool) -> &mut Self {
+        self.0.append(append);
+        self
+    }
+
+    pub fn truncate(&mut self, truncate: bool) -> &mut Self {
+        self.0.truncate(truncate);
+        self
+    }
+
+    pub fn create(&mut self, create: bool) -> &mut Self {
+        self.0.create(create);
+        self
+    }
+
+    pub async fn open(&self, path: impl AsRef<Path>) -> io::Result<File> {
+        let path: PathBuf = path.as_ref().to_owned();
+        let options = self.0.clone();
+        asyncify(move || options.open(path)).await.map(File::from_std)
+    }
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+enum Operation {
+    Read(io::Result<usize>),
+    Write(io::Result<()>),
+}
+
+/// A file whose reads and writes run on the blocking pool.
+///
+/// Writes are handed off in the background, so an error from a write is reported by the
+/// next operation on the file. Call `flush` or `sync_all` to observe it.
+pub struct File {
+    std: Arc<fs::File>,
+    buf: Vec<u8>,
+    pos: usize,
+    eof: bool,
+    inflight: Option<JoinHandle<(Operation, Vec<u8>)>>,
+}
+
+impl File {
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<File> {
+        OpenOptions::new().read(true).open(path).await
+    }
+
+    pub async fn create(path: impl AsRef<Path>) -> io::Result<File> {
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .await
+    }
+
+    pub fn options() -> OpenOptions {
+        OpenOptions::new()
+    }
+
+    pub fn from_std(std: fs::File) -> File {
+        File {
+            std: Arc::new(std),
+            buf: Vec::new(),
+            pos: 0,
+            eof: false,
+            inflight: None,
+        }
+    }
+
+    pub async fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        future::poll_fn(|cx| self.poll_idle(cx)).await?;
+        // Bytes read ahead but not handed out yet sit before the real cursor position.
+        let pos = match pos {
+            SeekFrom::Current(offset) => SeekFrom::Current(offset - self.remaining() as i64),
+            pos => pos,
+        };
+        self.discard_buffer();
+        let std = self.std.clone();
+        asyncify(move || (&*std).seek(pos)).await
+    }
+
+    pub async fn sync_all(&mut self) -> io::Result<()> {
+        future::poll_fn(|cx| self.poll_idle(cx)).await?;
+        let std = self.std.clone();
+        asyncify(move || std.sync_all()).await
+    }
+
+    pub async fn metadata(&self) -> io::Result<Metadata> {
+        let std = self.std.clone();
+        asyncify(move || std.metadata()).await
+    }
+
+    fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    fn discard_buffer(&mut self) {
+        self.buf.clear();
+        self.pos = 0;
+        self.eof = false;
+    }
+
+    // Waits for the operation handed to the blocking pool, if any, to finish.
+    fn poll_idle(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        builder::io_enabled()?;
+        let handle = match self.inflight.as_mut() {
+            Some(handle) => handle,
+            None => return Poll::Ready(Ok(())),
+        };
+        let result = ready!(Pin::new(handle).poll(cx));
+        self.inflight = None;
+        let (operation, mut data) = result?;
+        match operation {
+            Operation::Read(Ok(n)) => {
+                data.truncate(n);
+                self.buf = data;
+                self.pos = 0;
+                self.eof = n == 0;
+                Poll::Ready(Ok(()))
+            }
+            Operation::Write(Ok(())) => {
+                self.buf = data;
+                self.discard_buffer();
+                Poll::Ready(Ok(()))
+            }
+            Operation::Read(Err(e)) | Operation::Write(Err(e)) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+impl AsyncRead for File {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            ready!(this.poll_idle(cx))?;
+            if this.remaining() > 0 || this.eof || buf.is_empty() {
+                let n = this.remaining().min(buf.len());
+                buf[..n].copy_from_slice(&this.buf[this.pos..this.pos + n]);
+                this.pos += n;
+                this.eof = false;
+                return Poll::Ready(Ok(n));
+            }
+            let mut data = std::mem::take(&mut this.buf);
+            data.resize(buf.len().min(MAX_BUF), 0);
+            let std = this.std.clone();
+            this.inflight = Some(spawn_blocking(move || {
+                let result = (&*std).read(&mut data);
+                (Operation::Read(result), data)
+            }));
+        }
+    }
+}
+
+impl AsyncWrite for File {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_idle(cx))?;
+        let seek_back = this.remaining() as i64;
+        let n = buf.len().min(MAX_BUF);
+        this.discard_buffer();
+        let mut data = std::mem::take(&mut this.buf);
+        data.extend_from_slice(&buf[..n]);
+        let std = this.std.clone();
+        this.inflight = Some(spawn_blocking(move || {
+            let result = match seek_back {
+                0 => (&*std).write_all(&data),
+                _ => (&*std)
+                    .seek(SeekFrom::Current(-seek_back))
+                    .and_then(|_| (&*std).write_all(&data)),
+            };
+            (Operation::Write(result), data)
+        }));
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_idle(cx)
+    }
+}
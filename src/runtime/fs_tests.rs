@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::fs::{self, File, OpenOptions};
+    use crate::runtime::io::{AsyncReadExt, AsyncWriteExt};
+    use crate::runtime::test_util::run;
+    use std::io::SeekFrom;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("std_async_fs_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_write_and_read_to_string() {
+        let path = temp_path("write_read");
+        let contents = run({
+            let path = path.clone();
+            async move {
+                fs::write(&path, "Hello, file!").await?;
+                fs::read_to_string(&path).await
+            }
+        });
+        assert_eq!(contents.unwrap(), "Hello, file!");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_file_create_write_read() {
+        let path = temp_path("file_rw");
+        let result = run({
+            let path = path.clone();
+            async move {
+                let mut file = File::create(&path).await?;
+                file.write_all(b"first line\n").await?;
+                file.write_all(&vec![b'x'; 100_000]).await?;
+                file.sync_all().await?;
+
+                let mut file = File::open(&path).await?;
+                let mut contents = Vec::new();
+                file.read_to_end(&mut contents).await?;
+                Ok::<_, std::io::Error>(contents)
+            }
+        });
+        let contents = result.unwrap();
+        assert_eq!(contents.len(), 11 + 100_000);
+        assert!(contents.starts_with(b"first line\n"));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_file_seek() {
+        let path = temp_path("seek");
+        std::fs::write(&path, b"0123456789").unwrap();
+        let result = run({
+            let path = path.clone();
+            async move {
+                let mut file = File::open(&path).await?;
+                let mut buf = [0u8; 2];
+                file.read_exact(&mut buf).await?;
+                assert_eq!(&buf, b"01");
+
+                // Current-relative seeks account for bytes already read ahead.
+                let pos = file.seek(SeekFrom::Current(3)).await?;
+                assert_eq!(pos, 5);
+                file.read_exact(&mut buf).await?;
+                assert_eq!(&buf, b"56");
+
+                file.seek(SeekFrom::Start(1)).await?;
+                file.read_exact(&mut buf).await?;
+                Ok::<_, std::io::Error>(buf)
+            }
+        });
+        assert_eq!(&result.unwrap(), b"12");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_open_options_append() {
+        let path = temp_path("append");
+        std::fs::write(&path, b"one\n").unwrap();
+        let result = run({
+            let path = path.clone();
+            async move {
+                let mut file = OpenOptions::new().append(true).open(&path).await?;
+                file.write_all(b"two\n").await?;
+                file.flush().await?;
+                fs::read_to_string(&path).await
+            }
+        });
+        assert_eq!(result.unwrap(), "one\ntwo\n");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_create_dir_all_and_metadata() {
+        let dir = temp_path("dirs");
+        let nested = dir.join("a").join("b");
+        let result = run({
+            let nested = nested.clone();
+            async move {
+                fs::create_dir_all(&nested).await?;
+                fs::metadata(&nested).await
+            }
+        });
+        assert!(result.unwrap().is_dir());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_open_missing_file() {
+        let path = temp_path("missing");
+        let result = run(async move { File::open(&path).await.map(|_| ()) });
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+}
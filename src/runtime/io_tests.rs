@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+    use crate::runtime::test_util::run;
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    // Hands out at most `chunk` bytes per read and returns Pending every other poll.
+    struct Chunked {
+        data: Vec<u8>,
+        pos: usize,
+        chunk: usize,
+        ready: bool,
+    }
+
+    impl Chunked {
+        fn new(data: &[u8], chunk: usize) -> Self {
+            Chunked {
+                data: data.to_vec(),
+                pos: 0,
+                chunk,
+                ready: false,
+            }
+        }
+
+        fn not_ready(&mut self, cx: &mut Context<'_>) -> bool {
+            self.ready = !self.ready;
+            if !self.ready {
+                return false;
+            }
+            cx.waker().wake_by_ref();
+            true
+        }
+    }
+
+    impl AsyncRead for Chunked {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            if self.not_ready(cx) {
+                return Poll::Pending;
+            }
+            let n = (self.data.len() - self.pos).min(self.chunk).min(buf.len());
+            buf[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+            self.pos += n;
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    impl AsyncWrite for Chunked {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            if self.not_ready(cx) {
+                return Poll::Pending;
+            }
+            let n = buf.len().min(self.chunk);
+            self.data.extend_from_slice(&buf[..n]);
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn test_read_exact_across_chunks() {
+        let result = run(async {
+            let mut reader = Chunked::new(b"hello world", 3);
+            let mut buf = [0u8; 8];
+            reader.read_exact(&mut buf).await.map(|_| buf)
+        });
+        assert_eq!(&result.unwrap(), b"hello wo");
+    }
+
+    #[test]
+    fn test_read_exact_unexpected_eof() {
+        let result = run(async {
+            let mut reader = Chunked::new(b"abc", 2);
+            let mut buf = [0u8; 8];
+            reader.read_exact(&mut buf).await
+        });
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_read_to_end_and_to_string() {
+        let (bytes, text) = run(async {
+            let mut bytes = Vec::new();
+            let len = Chunked::new(b"0123456789", 4)
+                .read_to_end(&mut bytes)
+                .await
+                .unwrap();
+            assert_eq!(len, 10);
+
+            let mut text = String::from(">");
+            Chunked::new("héllo".as_bytes(), 1)
+                .read_to_string(&mut text)
+                .await
+                .unwrap();
+            (bytes, text)
+        });
+        assert_eq!(bytes, b"0123456789");
+        assert_eq!(text, ">héllo");
+    }
+
+    #[test]
+    fn test_read_to_string_invalid_utf8() {
+        let result = run(async {
+            let mut text = String::new();
+            Chunked::new(&[0xFF, 0xFE], 8).read_to_string(&mut text).await
+        });
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_write_all_across_chunks() {
+        let written = run(async {
+            let mut writer = Chunked::new(b"", 2);
+            writer.write_all(b"partial writes").await.unwrap();
+            writer.flush().await.unwrap();
+            writer.shutdown().await.unwrap();
+            writer.data
+        });
+        assert_eq!(written, b"partial writes");
+    }
+}
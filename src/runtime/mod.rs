@@ -1,16 +1,74 @@
+pub mod blocking;
+pub mod builder;
+pub mod clock;
+pub mod coop;
+pub mod dump;
 pub mod executor;
+pub mod fs;
+pub mod io;
+#[cfg(unix)]
+pub mod process;
 pub mod reciever;
+pub mod schedule;
+pub mod scope;
 pub mod sender;
+#[cfg(unix)]
+pub mod signal;
 pub mod sleep;
+#[cfg(unix)]
+pub(crate) mod sys;
+pub mod task_local;
+mod timer;
+pub mod udp;
+#[cfg(unix)]
+pub mod unix;
 pub mod waker;
+pub mod watchdog;
 
+pub use blocking::spawn_blocking;
+pub use builder::{Builder, Handle, Runtime};
+pub use coop::yield_now;
+pub use scope::scope;
+
+#[cfg(test)]
+mod blocking_tests;
+#[cfg(test)]
+mod builder_tests;
+#[cfg(test)]
+mod clock_tests;
+#[cfg(test)]
+mod coop_tests;
+#[cfg(test)]
+mod dump_tests;
 #[cfg(test)]
 mod executor_tests;
 #[cfg(test)]
+mod fs_tests;
+#[cfg(test)]
+mod io_tests;
+#[cfg(all(test, unix))]
+mod process_tests;
+#[cfg(test)]
 mod reciever_tests;
 #[cfg(test)]
+mod schedule_tests;
+#[cfg(test)]
+mod scope_tests;
+#[cfg(test)]
 mod sender_tests;
+#[cfg(all(test, unix))]
+mod signal_tests;
 #[cfg(test)]
 mod sleep_tests;
 #[cfg(test)]
+mod task_local_tests;
+#[cfg(test)]
+mod test_util;
+#[cfg(test)]
+mod udp_tests;
+#[cfg(all(test, unix))]
+mod unix_tests;
+#[cfg(test)]
 mod waker_tests;
+#[cfg(test)]
+mod watchdog_tests;
@@ -0,0 +1,457 @@
+use crate::runtime::blocking::spawn_blocking;
+use crate::runtime::clock::{Clock, Outstanding};
+use crate::runtime::io::{AsyncRead, AsyncReadExt, AsyncWrite, poll_would_block};
+use crate::runtime::signal::{Signal, SignalKind, signal};
+use crate::runtime::sys::{self, POLLIN, PollFd};
+use std::{
+    ffi::OsStr,
+    future::{self, Future},
+    io::{self, Read, Write},
+    os::fd::{AsRawFd, OwnedFd, RawFd},
+    os::unix::net::UnixStream,
+    path::Path,
+    pin::{Pin, pin},
+    process::{self, ExitStatus, Output, Stdio},
+    sync::{Mutex, OnceLock},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+/// Async counterpart of [`std::process::Command`].
+pub struct Command {
+    std: process::Command,
+    kill_on_drop: bool,
+    stdin_set: bool,
+    stdout_set: bool,
+    stderr_set: bool,
+}
+
+impl Command {
+    pub fn new(program: impl AsRef<OsStr>) -> Self {
+        Command {
+            std: process::Command::new(program),
+            kill_on_drop: false,
+            stdin_set: false,
+            stdout_set: false,
+            stderr_set: false,
+        }
+    }
+
+    pub fn arg(&mut self, arg: impl AsRef<OsStr>) -> &mut Self {
+        self.std.arg(arg);
+        self
+    }
+
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.std.args(args);
+        self
+    }
+
+    pub fn env(&mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> &mut Self {
+        self.std.env(key, value);
+        self
+    }
+
+    pub fn env_remove(&mut self, key: impl AsRef<OsStr>) -> &mut Self {
+        self.std.env_remove(key);
+        self
+    }
+
+    pub fn env_clear(&mut self) -> &mut Self {
+        self.std.env_clear();
+        self
+    }
+
+    pub fn current_dir(&mut self, dir: impl AsRef<Path>) -> &mut Self {
+        self.std.current_dir(dir);
+        self
+    }
+
+    pub fn stdin(&mut self, cfg: impl Into<Stdio>) -> &mut Self {
+        self.std.stdin(cfg);
+        self.stdin_set = true;
+        self
+    }
+
+    pub fn stdout(&mut self, cfg: impl Into<Stdio>) -> &mut Self {
+        self.std.stdout(cfg);
+        self.stdout_set = true;
+        self
+    }
+
+    pub fn stderr(&mut self, cfg: impl Into<Stdio>) -> &mut Self {
+        self.std.stderr(cfg);
+        self.stderr_set = true;
+        self
+    }
+
+    /// Kills the child when its [`Child`] handle is dropped before it has exited.
+    pub fn kill_on_drop(&mut self, kill_on_drop: bool) -> &mut Self {
+        self.kill_on_drop = kill_on_drop;
+        self
+    }
+
+    pub fn spawn(&mut self) -> io::Result<Child> {
+        let mut child = self.std.spawn()?;
+        let stdin = child.stdin.take().map(ChildStdin::new).transpose()?;
+        let stdout = child.stdout.take().map(ChildStdout::new).transpose()?;
+        let stderr = child.stderr.take().map(ChildStderr::new).transpose()?;
+        // Without a pidfd, waits check `try_wait` whenever SIGCHLD arrives.
+        let pidfd = sys::pidfd_open(child.id()).ok();
+        Ok(Child {
+            child: Some(child),
+            pidfd,
+            sigchld: None,
+            outstanding: None,
+            kill_on_drop: self.kill_on_drop,
+            stdin,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Runs the command with inherited stdio and waits for it to exit.
+    pub async fn status(&mut self) -> io::Result<ExitStatus> {
+        let mut child = self.spawn()?;
+        drop(child.stdin.take());
+        drop(child.stdout.take());
+        drop(child.stderr.take());
+        child.wait().await
+    }
+
+    /// Runs the command, capturing stdout and stderr, and waits for it to exit.
+    pub async fn output(&mut self) -> io::Result<Output> {
+        if !self.stdin_set {
+            self.std.stdin(Stdio::null());
+        }
+        if !self.stdout_set {
+            self.std.stdout(Stdio::piped());
+        }
+        if !self.stderr_set {
+            self.std.stderr(Stdio::piped());
+        }
+        self.spawn()?.wait_with_output().await
+    }
+}
+
+pub struct Child {
+    child: Option<process::Child>,
+    pub(crate) pidfd: Option<OwnedFd>,
+    sigchld: Option<Signal>,
+    // Held while waiting on SIGCHLD, as the reaper does for the children it watches.
+    outstanding: Option<Outstanding>,
+    kill_on_drop: bool,
+    pub stdin: Option<ChildStdin>,
+    pub stdout: Option<ChildStdout>,
+    pub stderr: Option<ChildStderr>,
+}
+
+impl Child {
+    fn inner(&mut self) -> &mut process::Child {
+        self.child.as_mut().expect("child is only taken on drop")
+    }
+
+    pub fn id(&self) -> u32 {
+        self.child.as_ref().map(process::Child::id).unwrap_or_default()
+    }
+
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.inner().try_wait()
+    }
+
+    /// Sends SIGKILL without waiting for the child to exit.
+    pub fn start_kill(&mut self) -> io::Result<()> {
+        self.inner().kill()
+    }
+
+    pub async fn kill(&mut self) -> io::Result<()> {
+        self.start_kill()?;
+        self.wait().await.map(|_| ())
+    }
+
+    /// Waits for the child to exit. Close `stdin` first if the child reads until EOF.
+    pub async fn wait(&mut self) -> io::Result<ExitStatus> {
+        future::poll_fn(|cx| self.poll_wait(cx)).await
+    }
+
+    pub async fn wait_with_output(mut self) -> io::Result<Output> {
+        drop(self.stdin.take());
+        let mut stdout_pipe = self.stdout.take();
+        let mut stderr_pipe = self.stderr.take();
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        {
+            // Both pipes are drained together so a child filling one cannot stall on it.
+            let mut read_stdout = pin!(read_all(stdout_pipe.as_mut(), &mut stdout));
+            let mut read_stderr = pin!(read_all(stderr_pipe.as_mut(), &mut stderr));
+            let mut stdout_done = false;
+            let mut stderr_done = false;
+            future::poll_fn(|cx| {
+                if !stdout_done
+                    && let Poll::Ready(result) = read_stdout.as_mut().poll(cx)
+                {
+                    result?;
+                    stdout_done = true;
+                }
+                if !stderr_done
+                    && let Poll::Ready(result) = read_stderr.as_mut().poll(cx)
+                {
+                    result?;
+                    stderr_done = true;
+                }
+                if stdout_done && stderr_done {
+                    Poll::Ready(Ok::<_, io::Error>(()))
+                } else {
+                    Poll::Pending
+                }
+            })
+            .await?;
+        }
+        let status = self.wait().await?;
+        Ok(Output {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+
+    fn poll_wait(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<ExitStatus>> {
+        if self.pidfd.is_none() && self.sigchld.is_none() {
+            // Listen before the first check, so an exit right after it is not missed.
+            self.sigchld = Some(signal(SignalKind::CHILD)?);
+        }
+        loop {
+            if let Some(status) = self.inner().try_wait()? {
+                self.outstanding = None;
+                return Poll::Ready(Ok(status));
+            }
+            if let Some(pidfd) = &self.pidfd {
+                reaper().register(pidfd.as_raw_fd(), cx.waker().clone())?;
+                return Poll::Pending;
+            }
+            // SIGCHLD is raised for any child, so check again and keep waiting if it was
+            // not this one.
+            let sigchld = self.sigchld.as_mut().expect("set above without a pidfd");
+            if sigchld.poll_recv(cx).is_pending() {
+                self.outstanding
+                    .get_or_insert_with(|| Clock::current().outstanding());
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        if let Some(pidfd) = &self.pidfd {
+            reaper().deregister(pidfd.as_raw_fd());
+        }
+        let mut child = match self.child.take() {
+            Some(child) => child,
+            None => return,
+        };
+        if self.kill_on_drop && matches!(child.try_wait(), Ok(None)) {
+            let _ = child.kill();
+            // Reap it off the executor thread so it does not linger as a zombie.
+            drop(spawn_blocking(move || child.wait()));
+        }
+    }
+}
+
+async fn read_all<R: AsyncRead + Unpin>(pipe: Option<&mut R>, buf: &mut Vec<u8>) -> io::Result<()> {
+    if let Some(pipe) = pipe {
+        pipe.read_to_end(buf).await?;
+    }
+    Ok(())
+}
+
+/// Write end of the child's stdin. Drop it to signal EOF to the child.
+pub struct ChildStdin {
+    inner: process::ChildStdin,
+}
+
+pub struct ChildStdout {
+    inner: process::ChildStdout,
+}
+
+pub struct ChildStderr {
+    inner: process::ChildStderr,
+}
+
+impl ChildStdin {
+    fn new(inner: process::ChildStdin) -> io::Result<Self> {
+        sys::set_nonblocking(&inner, true)?;
+        Ok(ChildStdin { inner })
+    }
+}
+
+impl ChildStdout {
+    fn new(inner: process::ChildStdout) -> io::Result<Self> {
+        sys::set_nonblocking(&inner, true)?;
+        Ok(ChildStdout { inner })
+    }
+}
+
+impl ChildStderr {
+    fn new(inner: process::ChildStderr) -> io::Result<Self> {
+        sys::set_nonblocking(&inner, true)?;
+        Ok(ChildStderr { inner })
+    }
+}
+
+impl AsyncWrite for ChildStdin {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        poll_would_block(cx, || self.inner.write(buf))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        poll_would_block(cx, || self.inner.flush())
+    }
+}
+
+impl AsyncRead for ChildStdout {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        poll_would_block(cx, || self.inner.read(buf))
+    }
+}
+
+impl AsyncRead for ChildStderr {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        poll_would_block(cx, || self.inner.read(buf))
+    }
+}
+
+// Background thread that sleeps in poll(2) on the pidfds of children being awaited and
+// wakes their tasks once they exit.
+pub(crate) struct Reaper {
+    // Each waiter keeps a simulated clock from skipping ahead until its child exits.
+    waiters: Mutex<Vec<(RawFd, Waker, Outstanding)>>,
+    // Why the thread stopped, after which waits fail instead of hanging. Only set with
+    // `waiters` locked.
+    failed: OnceLock<io::Error>,
+    notify: UnixStream,
+}
+
+static REAPER: OnceLock<Reaper> = OnceLock::new();
+
+fn reaper() -> &'static Reaper {
+    REAPER.get_or_init(|| {
+        let (notify, wakeup) = UnixStream::pair().expect("failed to create reaper wakeup pair");
+        notify
+            .set_nonblocking(true)
+            .expect("failed to configure reaper wakeup pair");
+        wakeup
+            .set_nonblocking(true)
+            .expect("failed to configure reaper wakeup pair");
+        thread::Builder::new()
+            .name(String::from("process-reaper"))
+            .spawn(move || {
+                let reaper = REAPER.wait();
+                reaper.run(wakeup);
+            })
+            .expect("failed to spawn process reaper thread");
+        Reaper::new(notify)
+    })
+}
+
+impl Reaper {
+    pub(crate) fn new(notify: UnixStream) -> Reaper {
+        Reaper {
+            waiters: Mutex::new(Vec::new()),
+            failed: OnceLock::new(),
+            notify,
+        }
+    }
+
+    /// Wakes `waker` once `pidfd` becomes readable, i.e. the child has exited. Fails if
+    /// the reaper thread has died.
+    pub(crate) fn register(&self, pidfd: RawFd, waker: Waker) -> io::Result<()> {
+        let mut waiters = self.waiters.lock().unwrap();
+        if let Some(e) = self.failed.get() {
+            return Err(io::Error::new(e.kind(), format!("process reaper failed: {}", e)));
+        }
+        match waiters.iter_mut().find(|(fd, _, _)| *fd == pidfd) {
+            Some((_, existing, _)) => *existing = waker,
+            None => waiters.push((pidfd, waker, Clock::current().outstanding())),
+        }
+        drop(waiters);
+        self.wakeup();
+        Ok(())
+    }
+
+    /// Marks the reaper dead and wakes every waiter, whose next poll sees the error.
+    pub(crate) fn fail(&self, e: io::Error) {
+        let mut waiters = self.waiters.lock().unwrap();
+        let _ = self.failed.set(e);
+        for (_, waker, outstanding) in waiters.drain(..) {
+            waker.wake();
+            drop(outstanding);
+        }
+    }
+
+    fn deregister(&self, pidfd: RawFd) {
+        self.waiters.lock().unwrap().retain(|(fd, _, _)| *fd != pidfd);
+        self.wakeup();
+    }
+
+    fn wakeup(&self) {
+        // A full buffer already guarantees the thread will wake, so WouldBlock is fine.
+        let _ = (&self.notify).write(&[1]);
+    }
+
+    fn run(&self, mut wakeup: UnixStream) {
+        loop {
+            let mut fds = vec![PollFd::new(wakeup.as_raw_fd(), POLLIN)];
+            fds.extend(
+                self.waiters
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(fd, _, _)| PollFd::new(*fd, POLLIN)),
+            );
+            if let Err(e) = sys::poll(&mut fds, -1) {
+                self.fail(e);
+                return;
+            }
+            if fds[0].is_ready() {
+                let mut drain = [0u8; 64];
+                while matches!(wakeup.read(&mut drain), Ok(n) if n > 0) {}
+            }
+            let exited: Vec<RawFd> = fds[1..]
+                .iter()
+                .filter(|fd| fd.is_ready())
+                .map(|fd| fd.fd)
+                .collect();
+            if exited.is_empty() {
+                continue;
+            }
+            let mut waiters = self.waiters.lock().unwrap();
+            let mut index = 0;
+            while index < waiters.len() {
+                if exited.contains(&waiters[index].0) {
+                    let (_, waker, outstanding) = waiters.swap_remove(index);
+                    waker.wake();
+                    drop(outstanding);
+                } else {
+                    index += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::executor::Executor;
+    use crate::runtime::io::{AsyncReadExt, AsyncWriteExt};
+    use crate::runtime::process::{Command, Reaper};
+    use crate::runtime::test_util::run;
+    use crate::runtime::waker::TaskWaker;
+    use std::future::{self, Future};
+    use std::os::unix::net::UnixStream;
+    use std::pin::pin;
+    use std::process::Stdio;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::Waker;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn process_exists(pid: u32) -> bool {
+        // Zombies still have a /proc entry, so check the state column too.
+        match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            Ok(stat) => !stat.contains(") Z "),
+            Err(_) => false,
+        }
+    }
+
+    #[test]
+    fn test_status_reports_exit_code() {
+        let status = run(async { Command::new("sh").args(["-c", "exit 3"]).status().await });
+        assert_eq!(status.unwrap().code(), Some(3));
+    }
+
+    #[test]
+    fn test_output_captures_stdout_and_stderr() {
+        let output = run(async {
+            Command::new("sh")
+                .args(["-c", "echo out; echo err >&2"])
+                .output()
+                .await
+        })
+        .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[test]
+    fn test_output_larger_than_pipe_buffer() {
+        let output = run(async {
+            Command::new("sh")
+                .args(["-c", "head -c 200000 /dev/zero"])
+                .output()
+                .await
+        })
+        .unwrap();
+        assert_eq!(output.stdout.len(), 200_000);
+    }
+
+    #[test]
+    fn test_piped_stdin_and_stdout() {
+        let result = run(async {
+            let mut child = Command::new("cat")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()?;
+            let mut stdin = child.stdin.take().unwrap();
+            stdin.write_all(b"echoed through cat").await?;
+            drop(stdin);
+
+            let mut stdout = child.stdout.take().unwrap();
+            let mut echoed = String::new();
+            stdout.read_to_string(&mut echoed).await?;
+            let status = child.wait().await?;
+            Ok::<_, std::io::Error>((echoed, status.success()))
+        });
+        assert_eq!(result.unwrap(), ("echoed through cat".to_string(), true));
+    }
+
+    #[test]
+    fn test_wait_does_not_block_executor() {
+        let mut executor = Executor::new();
+        let start = Instant::now();
+        let waiting = executor.spawn(async {
+            Command::new("sleep").arg("0.2").status().await
+        });
+        let quick = executor.spawn(async { Instant::now() });
+
+        let mut quick_done = None;
+        for _ in 0..20000 {
+            executor.poll();
+            if let Ok(at) = quick.try_recv() {
+                quick_done = Some(at);
+            }
+            if let Ok(status) = waiting.try_recv() {
+                assert!(status.unwrap().success());
+                let quick_done = quick_done.expect("quick task should finish first");
+                assert!(quick_done.duration_since(start) < Duration::from_millis(100));
+                return;
+            }
+            thread::sleep(Duration::from_micros(100));
+        }
+        panic!("Child did not exit");
+    }
+
+    #[test]
+    fn test_wait_without_pidfd_sleeps_until_sigchld() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let counted = polls.clone();
+        let status = run(async move {
+            let mut child = Command::new("sleep").arg("0.2").spawn()?;
+            // Take the fallback used where pidfds are unavailable.
+            child.pidfd = None;
+            let mut wait = pin!(child.wait());
+            future::poll_fn(|cx| {
+                counted.fetch_add(1, Ordering::SeqCst);
+                wait.as_mut().poll(cx)
+            })
+            .await
+        });
+        assert!(status.unwrap().success());
+        // Polling `try_wait` in a loop would take thousands; children exiting in other tests
+        // may add a few.
+        let polls = polls.load(Ordering::SeqCst);
+        assert!(polls < 50, "polled {} times", polls);
+    }
+
+    #[test]
+    fn test_kill_on_drop() {
+        let pid = run(async {
+            let child = Command::new("sleep")
+                .arg("30")
+                .kill_on_drop(true)
+                .spawn()
+                .unwrap();
+            child.id()
+        });
+
+        for _ in 0..500 {
+            if !process_exists(pid) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("Child was not killed on drop");
+    }
+
+    #[test]
+    fn test_kill() {
+        let status = run(async {
+            let mut child = Command::new("sleep").arg("30").spawn()?;
+            child.kill().await?;
+            child.wait().await
+        });
+        assert!(!status.unwrap().success());
+    }
+
+    #[test]
+    fn test_failed_reaper_wakes_waiters_and_rejects_new_ones() {
+        let (notify, _wakeup) = UnixStream::pair().unwrap();
+        notify.set_nonblocking(true).unwrap();
+        let reaper = Reaper::new(notify);
+        let state = TaskWaker::new();
+        reaper.register(3, Waker::from(state.clone())).unwrap();
+
+        reaper.fail(std::io::Error::other("poll failed"));
+        assert!(state.is_woken());
+        let err = reaper.register(4, Waker::noop().clone()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+        assert!(err.to_string().contains("poll failed"));
+    }
+
+    #[test]
+    fn test_spawn_missing_program() {
+        let result = Command::new("definitely-not-a-real-program").spawn();
+        assert_eq!(result.err().unwrap().kind(), std::io::ErrorKind::NotFound);
+    }
+}
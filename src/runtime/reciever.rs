@@ -1,19 +1,25 @@
+use crate::runtime::{builder, coop, sender::SocketStream};
 use std::{
     future::Future,
-    io::{self, Read},
+    io,
     net::TcpStream,
     pin::Pin,
     sync::{Arc, Mutex},
-    task::{Context, Poll},
+    task::{Context, Poll, ready},
 };
 
-pub struct TcpReceiver {
-    pub stream: Arc<Mutex<TcpStream>>,
+pub type TcpReceiver = StreamReceiver<TcpStream>;
+
+/// Reads from the stream until the peer closes it.
+pub struct StreamReceiver<S> {
+    pub stream: Arc<Mutex<S>>,
     pub buffer: Vec<u8>,
 }
-impl Future for TcpReceiver {
+impl<S: SocketStream> Future for StreamReceiver<S> {
     type Output = io::Result<Vec<u8>>;
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        builder::io_enabled()?;
+        let coop = ready!(coop::poll_proceed(cx));
         let mut stream = match self.stream.try_lock() {
             Ok(stream) => stream,
             Err(_) => {
@@ -24,8 +30,12 @@ impl Future for TcpReceiver {
         stream.set_nonblocking(true)?;
         let mut local_buf = [0; 1024];
         match stream.read(&mut local_buf) {
-            Ok(0) => Poll::Ready(Ok(self.buffer.to_vec())),
+            Ok(0) => {
+                coop.made_progress();
+                Poll::Ready(Ok(self.buffer.to_vec()))
+            }
             Ok(n) => {
+                coop.made_progress();
                 std::mem::drop(stream); // Drop here 
                 self.buffer.extend_from_slice(&local_buf[..n]);
                 cx.waker().wake_by_ref();
@@ -35,7 +45,10 @@ impl Future for TcpReceiver {
                 cx.waker().wake_by_ref();
                 Poll::Pending
             }
-            Err(e) => Poll::Ready(Err(e)),
+            Err(e) => {
+                coop.made_progress();
+                Poll::Ready(Err(e))
+            }
         }
     }
 }
@@ -0,0 +1,139 @@
+use crate::runtime::executor::{Executor, Task, TaskId};
+use std::{
+    any::Any,
+    collections::VecDeque,
+    error::Error,
+    fmt,
+    ops::Range,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// How the executor picks the next task to poll.
+pub(crate) enum Scheduler {
+    Fifo,
+    Seeded(Rng),
+    Replay { schedule: Vec<TaskId>, step: usize },
+}
+
+impl Scheduler {
+    pub(crate) fn records(&self) -> bool {
+        !matches!(self, Scheduler::Fifo)
+    }
+
+    /// Index into `queue` of the task to poll next, or `None` if no task is woken.
+    pub(crate) fn next_index(&mut self, queue: &VecDeque<Task>) -> Option<usize> {
+        match self {
+            Scheduler::Fifo => queue.iter().position(Task::is_woken),
+            Scheduler::Seeded(rng) => {
+                let woken = queue.iter().filter(|task| task.is_woken()).count();
+                if woken == 0 {
+                    return None;
+                }
+                let pick = rng.below(woken);
+                queue
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, task)| task.is_woken())
+                    .nth(pick)
+                    .map(|(index, _)| index)
+            }
+            Scheduler::Replay { schedule, step } => {
+                let Some(&id) = schedule.get(*step) else {
+                    // Past the end of the recording, carry on in FIFO order.
+                    return queue.iter().position(Task::is_woken);
+                };
+                let Some(index) = queue.iter().position(|task| task.id() == id) else {
+                    panic!(
+                        "replayed schedule diverged at step {}: task {} is not runnable",
+                        *step, id
+                    );
+                };
+                // The recorded task was woken when it ran, so wait for that again.
+                if !queue[index].is_woken() {
+                    return None;
+                }
+                *step += 1;
+                Some(index)
+            }
+        }
+    }
+}
+
+/// SplitMix64: tiny, fast and good enough for picking tasks.
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+/// A seed under which the test body panicked, with the schedule that led there.
+#[derive(Debug)]
+pub struct SeedFailure {
+    pub seed: u64,
+    pub message: String,
+    pub schedule: Vec<TaskId>,
+}
+
+impl fmt::Display for SeedFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed with seed {} after {} polls: {} (reproduce with Executor::seeded({}))",
+            self.seed,
+            self.schedule.len(),
+            self.message,
+            self.seed
+        )
+    }
+}
+
+impl Error for SeedFailure {}
+
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("non-string panic payload"))
+}
+
+/// Runs `body` once per seed on a freshly seeded executor, stopping at the first seed
+/// whose run panics, either in `body` itself or in a task it spawned.
+pub fn check_seeds<F>(seeds: Range<u64>, mut body: F) -> Result<(), SeedFailure>
+where
+    F: FnMut(&mut Executor),
+{
+    for seed in seeds {
+        let mut executor = Executor::seeded(seed);
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| body(&mut executor)));
+        // A task panic usually makes the body fail too, e.g. on a disconnected result
+        // channel, but the task's message is the one that explains it.
+        let message = match (executor.take_panic(), outcome) {
+            (Some((id, message)), _) => format!("task {} panicked: {}", id, message),
+            (None, Err(payload)) => panic_message(&*payload),
+            (None, Ok(())) => continue,
+        };
+        return Err(SeedFailure {
+            seed,
+            message,
+            schedule: executor.schedule().to_vec(),
+        });
+    }
+    Ok(())
+}
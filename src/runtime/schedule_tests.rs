@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::executor::{Executor, TaskId};
+    use crate::runtime::schedule::check_seeds;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll};
+
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    // Three tasks each log a few steps with yields in between.
+    fn interleaving(executor: &mut Executor) -> Vec<(u32, u32)> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        for task in 0..3 {
+            let log = log.clone();
+            executor.spawn(async move {
+                for step in 0..3 {
+                    log.lock().unwrap().push((task, step));
+                    YieldOnce(false).await;
+                }
+            });
+        }
+        executor.run();
+        log.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn test_fifo_executor_does_not_record() {
+        let mut executor = Executor::new();
+        interleaving(&mut executor);
+        assert!(executor.schedule().is_empty());
+    }
+
+    #[test]
+    fn test_same_seed_same_interleaving() {
+        let mut first = Executor::seeded(42);
+        let mut second = Executor::seeded(42);
+        assert_eq!(interleaving(&mut first), interleaving(&mut second));
+        assert_eq!(first.schedule(), second.schedule());
+        assert!(!first.schedule().is_empty());
+    }
+
+    #[test]
+    fn test_seeds_explore_different_interleavings() {
+        let fifo = interleaving(&mut Executor::new());
+        let differs = (0..20).any(|seed| interleaving(&mut Executor::seeded(seed)) != fifo);
+        assert!(differs, "No seed produced a non-FIFO interleaving");
+    }
+
+    #[test]
+    fn test_replay_reproduces_schedule() {
+        let mut seeded = Executor::seeded(7);
+        let expected = interleaving(&mut seeded);
+
+        let mut replay = Executor::replay(seeded.schedule().to_vec());
+        assert_eq!(interleaving(&mut replay), expected);
+        assert_eq!(replay.schedule(), seeded.schedule());
+    }
+
+    #[test]
+    #[should_panic(expected = "replayed schedule diverged")]
+    fn test_replay_divergence_panics() {
+        let mut replay = Executor::replay(vec![TaskId(5)]);
+        replay.spawn(async {});
+        replay.poll();
+    }
+
+    #[test]
+    fn test_check_seeds_reports_failing_seed() {
+        // Unsynchronized check-then-act: broken only when the reader runs between the
+        // writer's two steps.
+        let body = |executor: &mut Executor| {
+            let value = Arc::new(Mutex::new(0));
+            let writer = value.clone();
+            executor.spawn(async move {
+                *writer.lock().unwrap() = 1;
+                YieldOnce(false).await;
+                *writer.lock().unwrap() = 2;
+            });
+            let reader = value.clone();
+            let rx = executor.spawn(async move {
+                YieldOnce(false).await;
+                *reader.lock().unwrap()
+            });
+            executor.run();
+            let seen = rx.recv().unwrap();
+            assert!(seen != 1, "reader saw a half-finished update");
+        };
+
+        let failure = check_seeds(0..200, body).expect_err("some seed should expose the race");
+        assert!(failure.message.contains("half-finished update"));
+        assert!(failure.to_string().contains(&format!("seed {}", failure.seed)));
+
+        // Re-running the reported seed fails the same way.
+        let rerun = check_seeds(failure.seed..failure.seed + 1, body).unwrap_err();
+        assert_eq!(rerun.schedule, failure.schedule);
+    }
+
+    #[test]
+    fn test_check_seeds_catches_panics_inside_tasks() {
+        let failure = check_seeds(0..200, |executor| {
+            let value = Arc::new(Mutex::new(0));
+            let writer = value.clone();
+            executor.spawn(async move {
+                *writer.lock().unwrap() = 1;
+                YieldOnce(false).await;
+                *writer.lock().unwrap() = 2;
+            });
+            executor.spawn(async move {
+                YieldOnce(false).await;
+                let seen = *value.lock().unwrap();
+                assert!(seen != 1, "reader saw a half-finished update");
+            });
+            executor.run();
+        })
+        .expect_err("some seed should expose the race");
+        assert!(failure.message.starts_with("task #"));
+        assert!(failure.message.contains("half-finished update"));
+    }
+
+    #[test]
+    fn test_check_seeds_passes_for_correct_code() {
+        let result = check_seeds(0..50, |executor| {
+            let total = Arc::new(Mutex::new(0));
+            for _ in 0..4 {
+                let total = total.clone();
+                executor.spawn(async move {
+                    YieldOnce(false).await;
+                    *total.lock().unwrap() += 1;
+                });
+            }
+            executor.run();
+            assert_eq!(*total.lock().unwrap(), 4);
+        });
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    future::{self, Future},
+    io, mem,
+    panic::{self, AssertUnwindSafe},
+    pin::{Pin, pin},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+type Child<'env> = Pin<Box<dyn Future<Output = io::Result<()>> + Send + 'env>>;
+
+/// Handle for spawning children inside [`scope`]. Clones spawn into the same scope, so
+/// children can be handed one to spawn siblings.
+#[derive(Clone)]
+pub struct Scope<'env> {
+    children: Arc<Mutex<Vec<Child<'env>>>>,
+}
+
+impl<'env> Scope<'env> {
+    /// Spawns a child that runs alongside the scope body. Unlike
+    /// [`Executor::spawn`](crate::runtime::executor::Executor::spawn) the child may borrow
+    /// anything that outlives the scope.
+    pub fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = io::Result<()>> + Send + 'env,
+    {
+        self.children.lock().unwrap().push(Box::pin(future));
+    }
+
+    // Polls every child, dropping the ones that finish. On the first failure the remaining
+    // children are dropped too, cancelling them.
+    fn poll_children(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Polled outside the lock so children can spawn siblings.
+        let mut children = mem::take(&mut *self.children.lock().unwrap());
+        let mut index = 0;
+        while index < children.len() {
+            let child = &mut children[index];
+            let result = match panic::catch_unwind(AssertUnwindSafe(|| child.as_mut().poll(cx))) {
+                Ok(Poll::Pending) => {
+                    index += 1;
+                    continue;
+                }
+                Ok(Poll::Ready(result)) => result,
+                Err(_) => Err(io::Error::other("scoped task panicked")),
+            };
+            drop(children.swap_remove(index));
+            if let Err(e) = result {
+                drop(children);
+                self.children.lock().unwrap().clear();
+                return Poll::Ready(Err(e));
+            }
+        }
+        let mut pending = self.children.lock().unwrap();
+        if !pending.is_empty() {
+            // Spawned while we were polling; make sure they get a first poll.
+            cx.waker().wake_by_ref();
+        }
+        pending.append(&mut children);
+        if pending.is_empty() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Runs the future returned by `body` with a [`Scope`] whose children are polled by the
+/// same task, and completes only once the body and every child have finished.
+///
+/// If a child fails or panics, the body and all other children are dropped and the error
+/// is returned; a panic becomes an `io::Error`.
+///
+/// ```ignore
+/// let mut totals = [0; 2];
+/// let (left, right) = totals.split_at_mut(1);
+/// scope(|s| async move {
+///     s.spawn(async move { left[0] = 1; Ok(()) });
+///     s.spawn(async move { right[0] = 2; Ok(()) });
+/// })
+/// .await?;
+/// ```
+pub async fn scope<'env, F, Fut>(body: F) -> io::Result<Fut::Output>
+where
+    F: FnOnce(Scope<'env>) -> Fut,
+    Fut: Future + 'env,
+{
+    let scope = Scope {
+        children: Arc::new(Mutex::new(Vec::new())),
+    };
+    // Children may hold clones of the handle, so clear them even if we are dropped early.
+    let _release = Release(&scope);
+    let mut body = pin!(body(scope.clone()));
+    let mut output = None;
+    future::poll_fn(|cx| {
+        if output.is_none()
+            && let Poll::Ready(value) = body.as_mut().poll(cx)
+        {
+            output = Some(value);
+        }
+        match scope.poll_children(cx) {
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Ready(Ok(())) => match output.take() {
+                Some(value) => Poll::Ready(Ok(value)),
+                None => Poll::Pending,
+            },
+            Poll::Pending => Poll::Pending,
+        }
+    })
+    .await
+}
+
+struct Release<'a, 'env>(&'a Scope<'env>);
+
+impl Drop for Release<'_, '_> {
+    fn drop(&mut self) {
+        let children = mem::take(&mut *self.0.children.lock().unwrap());
+        drop(children);
+    }
+}
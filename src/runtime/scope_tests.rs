@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::test_util::run;
+    use crate::runtime::{scope, yield_now};
+    use std::io;
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    #[test]
+    fn test_children_borrow_from_the_parent() {
+        let totals = run(async {
+            let mut totals = [0u32; 3];
+            let words = vec!["a", "bb", "ccc"];
+            let (slots, words) = (&mut totals, &words);
+            scope(|s| async move {
+                for (total, word) in slots.iter_mut().zip(words) {
+                    s.spawn(async move {
+                        yield_now().await;
+                        *total = word.len() as u32;
+                        Ok(())
+                    });
+                }
+            })
+            .await
+            .unwrap();
+            totals
+        });
+        assert_eq!(totals, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_scope_waits_for_every_child() {
+        let (body, finished) = run(async {
+            let finished = AtomicU32::new(0);
+            let counter = &finished;
+            let body = scope(|s| async move {
+                for n in 0..4 {
+                    s.spawn(async move {
+                        for _ in 0..n {
+                            yield_now().await;
+                        }
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        Ok(())
+                    });
+                }
+                "body done"
+            })
+            .await
+            .unwrap();
+            (body, finished.load(Ordering::SeqCst))
+        });
+        assert_eq!(body, "body done");
+        assert_eq!(finished, 4);
+    }
+
+    #[test]
+    fn test_children_can_spawn_siblings() {
+        let finished = run(async {
+            let finished = AtomicU32::new(0);
+            let counter = &finished;
+            scope(|s| async move {
+                let inner = s.clone();
+                s.spawn(async move {
+                    inner.spawn(async move {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        Ok(())
+                    });
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                });
+            })
+            .await
+            .unwrap();
+            finished.load(Ordering::SeqCst)
+        });
+        assert_eq!(finished, 2);
+    }
+
+    #[test]
+    fn test_child_error_cancels_siblings() {
+        let (result, sibling_finished) = run(async {
+            let sibling_finished = AtomicBool::new(false);
+            let flag = &sibling_finished;
+            let result = scope(|s| async move {
+                s.spawn(async move {
+                    for _ in 0..100 {
+                        yield_now().await;
+                    }
+                    flag.store(true, Ordering::SeqCst);
+                    Ok(())
+                });
+                s.spawn(async {
+                    yield_now().await;
+                    Err(io::Error::other("child failed"))
+                });
+            })
+            .await;
+            (result, sibling_finished.load(Ordering::SeqCst))
+        });
+        assert_eq!(result.unwrap_err().to_string(), "child failed");
+        assert!(!sibling_finished);
+    }
+
+    #[test]
+    fn test_child_panic_becomes_an_error() {
+        let result = run(async {
+            scope(|s| async move {
+                s.spawn(async { panic!("boom") });
+                s.spawn(std::future::pending());
+            })
+            .await
+        });
+        assert_eq!(result.unwrap_err().to_string(), "scoped task panicked");
+    }
+}
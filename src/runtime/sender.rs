@@ -1,19 +1,51 @@
+use crate::runtime::{builder, coop};
 use std::{
     future::Future,
-    io::{self, Write},
-    net::{TcpStream, Shutdown},
+    io::{self, Read, Write},
+    net::{Shutdown, TcpStream},
     pin::Pin,
     sync::{Arc, Mutex},
-    task::{Context, Poll},
+    task::{Context, Poll, ready},
 };
 
-pub struct TcpSender {
-    pub stream: Arc<Mutex<TcpStream>>,
+/// A connected stream socket that [`StreamSender`] and
+/// [`StreamReceiver`](crate::runtime::reciever::StreamReceiver) can drive.
+pub trait SocketStream: Read + Write {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+    fn shutdown(&self, how: Shutdown) -> io::Result<()>;
+}
+
+impl SocketStream for TcpStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        TcpStream::set_nonblocking(self, nonblocking)
+    }
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        TcpStream::shutdown(self, how)
+    }
+}
+
+#[cfg(unix)]
+impl SocketStream for std::os::unix::net::UnixStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        std::os::unix::net::UnixStream::set_nonblocking(self, nonblocking)
+    }
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        std::os::unix::net::UnixStream::shutdown(self, how)
+    }
+}
+
+pub type TcpSender = StreamSender<TcpStream>;
+
+/// Writes all of `buffer` to the stream, then shuts down its write side.
+pub struct StreamSender<S> {
+    pub stream: Arc<Mutex<S>>,
     pub buffer: Vec<u8>,
 }
-impl Future for TcpSender {
+impl<S: SocketStream> Future for StreamSender<S> {
     type Output = io::Result<()>;
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        builder::io_enabled()?;
+        let coop = ready!(coop::poll_proceed(cx));
         let mut stream = match self.stream.try_lock() {
             Ok(stream) => stream,
             Err(_) => {
@@ -24,6 +56,7 @@ impl Future for TcpSender {
         stream.set_nonblocking(true)?;
         match stream.write_all(&self.buffer) {
             Ok(_) => {
+                coop.made_progress();
                 // Shutdown the write side of the connection to signal we're done sending
                 let _ = stream.shutdown(Shutdown::Write);
                 Poll::Ready(Ok(()))
@@ -32,7 +65,10 @@ impl Future for TcpSender {
                 cx.waker().wake_by_ref();
                 Poll::Pending
             }
-            Err(e) => Poll::Ready(Err(e)),
+            Err(e) => {
+                coop.made_progress();
+                Poll::Ready(Err(e))
+            }
         }
     }
 }
@@ -0,0 +1,224 @@
+use crate::runtime::{coop, sys};
+use std::{
+    future,
+    io::{self, Read},
+    os::fd::AsRawFd,
+    os::raw::c_int,
+    os::unix::net::UnixStream,
+    sync::{
+        Mutex, OnceLock,
+        atomic::{AtomicI32, AtomicUsize, Ordering},
+    },
+    task::{Context, Poll, Waker, ready},
+    thread,
+};
+
+const MAX_SIGNAL: usize = 65;
+
+// Touched from the signal handler, so these are plain atomics rather than anything lazily
+// initialized or locked.
+static RECEIVED: [AtomicUsize; MAX_SIGNAL] = [const { AtomicUsize::new(0) }; MAX_SIGNAL];
+static WAKEUP_FD: AtomicI32 = AtomicI32::new(-1);
+
+static REGISTRY: OnceLock<Registry> = OnceLock::new();
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SignalKind(c_int);
+
+impl SignalKind {
+    pub const HANGUP: SignalKind = SignalKind(1);
+    pub const INTERRUPT: SignalKind = SignalKind(2);
+    pub const QUIT: SignalKind = SignalKind(3);
+    pub const PIPE: SignalKind = SignalKind(13);
+    pub const ALARM: SignalKind = SignalKind(14);
+    pub const TERMINATE: SignalKind = SignalKind(15);
+    #[cfg(target_os = "linux")]
+    pub const USER_DEFINED1: SignalKind = SignalKind(10);
+    #[cfg(target_os = "linux")]
+    pub const USER_DEFINED2: SignalKind = SignalKind(12);
+    #[cfg(target_os = "linux")]
+    pub const CHILD: SignalKind = SignalKind(17);
+    #[cfg(target_os = "linux")]
+    pub const WINDOW_CHANGE: SignalKind = SignalKind(28);
+    #[cfg(not(target_os = "linux"))]
+    pub const USER_DEFINED1: SignalKind = SignalKind(30);
+    #[cfg(not(target_os = "linux"))]
+    pub const USER_DEFINED2: SignalKind = SignalKind(31);
+    #[cfg(not(target_os = "linux"))]
+    pub const CHILD: SignalKind = SignalKind(20);
+    #[cfg(not(target_os = "linux"))]
+    pub const WINDOW_CHANGE: SignalKind = SignalKind(28);
+
+    pub const fn from_raw(signum: i32) -> SignalKind {
+        SignalKind(signum)
+    }
+
+    pub const fn as_raw(&self) -> i32 {
+        self.0
+    }
+
+    // Signals that cannot be caught, or that indicate a fault a handler could not recover from.
+    fn is_forbidden(&self) -> bool {
+        #[cfg(target_os = "linux")]
+        const STOP: c_int = 19;
+        #[cfg(not(target_os = "linux"))]
+        const STOP: c_int = 17;
+        matches!(self.0, 4 | 8 | 9 | 11) || self.0 == STOP
+    }
+}
+
+extern "C" fn handler(signum: c_int) {
+    // The interrupted code may be about to read errno, which a failed `write` would clobber.
+    let errno = sys::errno();
+    if let Some(received) = RECEIVED.get(signum as usize) {
+        received.fetch_add(1, Ordering::SeqCst);
+    }
+    let fd = WAKEUP_FD.load(Ordering::SeqCst);
+    if fd >= 0 {
+        sys::write_byte(fd, signum as u8);
+    }
+    sys::set_errno(errno);
+}
+
+struct Slot {
+    installed: OnceLock<Result<(), i32>>,
+    waiters: Mutex<Vec<(usize, Waker)>>,
+}
+
+struct Registry {
+    slots: Vec<Slot>,
+    next_id: AtomicUsize,
+    // Kept open for the life of the process; the handler writes to its raw fd.
+    _wakeup: UnixStream,
+}
+
+fn registry() -> &'static Registry {
+    REGISTRY.get_or_init(|| {
+        let (wakeup, mut driver) = UnixStream::pair().expect("failed to create signal self-pipe");
+        wakeup
+            .set_nonblocking(true)
+            .expect("failed to configure signal self-pipe");
+        WAKEUP_FD.store(wakeup.as_raw_fd(), Ordering::SeqCst);
+        thread::Builder::new()
+            .name(String::from("signal-driver"))
+            .spawn(move || {
+                let registry = REGISTRY.wait();
+                let mut signums = [0u8; 64];
+                loop {
+                    let n = match driver.read(&mut signums) {
+                        Ok(0) => return,
+                        Ok(n) => n,
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(e) => {
+                            println!("Signal driver failed to read: {}", e);
+                            return;
+                        }
+                    };
+                    for &signum in &signums[..n] {
+                        if let Some(slot) = registry.slots.get(signum as usize) {
+                            for (_, waker) in slot.waiters.lock().unwrap().drain(..) {
+                                waker.wake();
+                            }
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn signal driver thread");
+        Registry {
+            slots: (0..MAX_SIGNAL)
+                .map(|_| Slot {
+                    installed: OnceLock::new(),
+                    waiters: Mutex::new(Vec::new()),
+                })
+                .collect(),
+            next_id: AtomicUsize::new(0),
+            _wakeup: wakeup,
+        }
+    })
+}
+
+/// Listens for `kind`. The process-wide handler is installed on first use and stays
+/// installed, replacing the default action for that signal.
+pub fn signal(kind: SignalKind) -> io::Result<Signal> {
+    let signum = kind.as_raw();
+    if signum <= 0 || signum as usize >= MAX_SIGNAL || kind.is_forbidden() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("signal {} cannot be handled", signum),
+        ));
+    }
+    let registry = registry();
+    let slot = &registry.slots[signum as usize];
+    slot.installed
+        .get_or_init(|| {
+            sys::install_signal_handler(signum, handler)
+                .map_err(|e| e.raw_os_error().unwrap_or_default())
+        })
+        .map_err(io::Error::from_raw_os_error)?;
+    Ok(Signal {
+        kind,
+        id: registry.next_id.fetch_add(1, Ordering::SeqCst),
+        seen: RECEIVED[signum as usize].load(Ordering::SeqCst),
+    })
+}
+
+/// Completes on the next SIGINT.
+pub async fn ctrl_c() -> io::Result<()> {
+    signal(SignalKind::INTERRUPT)?.recv().await;
+    Ok(())
+}
+
+/// A stream of deliveries of one signal. Deliveries that arrive between two `recv` calls
+/// are coalesced into one.
+pub struct Signal {
+    kind: SignalKind,
+    id: usize,
+    seen: usize,
+}
+
+impl Signal {
+    pub fn kind(&self) -> SignalKind {
+        self.kind
+    }
+
+    pub async fn recv(&mut self) {
+        future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let coop = ready!(coop::poll_proceed(cx));
+        let signum = self.kind.as_raw() as usize;
+        if self.take_received(signum) {
+            coop.made_progress();
+            return Poll::Ready(());
+        }
+        let mut waiters = registry().slots[signum].waiters.lock().unwrap();
+        match waiters.iter_mut().find(|(id, _)| *id == self.id) {
+            Some((_, waker)) => *waker = cx.waker().clone(),
+            None => waiters.push((self.id, cx.waker().clone())),
+        }
+        drop(waiters);
+        // A signal may have landed while registering.
+        if self.take_received(signum) {
+            coop.made_progress();
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+
+    fn take_received(&mut self, signum: usize) -> bool {
+        let received = RECEIVED[signum].load(Ordering::SeqCst);
+        if received == self.seen {
+            return false;
+        }
+        self.seen = received;
+        true
+    }
+}
+
+impl Drop for Signal {
+    fn drop(&mut self) {
+        let slot = &registry().slots[self.kind.as_raw() as usize];
+        slot.waiters.lock().unwrap().retain(|(id, _)| *id != self.id);
+    }
+}
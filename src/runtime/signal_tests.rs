@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::executor::Executor;
+    use crate::runtime::signal::{SignalKind, signal};
+    use std::process::Command;
+    use std::thread;
+    use std::time::Duration;
+
+    fn raise(kind: SignalKind) {
+        let status = Command::new("kill")
+            .arg(format!("-{}", kind.as_raw()))
+            .arg(std::process::id().to_string())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_signal_is_received() {
+        let mut executor = Executor::new();
+        let mut user1 = signal(SignalKind::USER_DEFINED1).unwrap();
+        let rx = executor.spawn(async move {
+            user1.recv().await;
+            "received"
+        });
+
+        for _ in 0..5 {
+            executor.poll();
+        }
+        assert!(rx.try_recv().is_err());
+
+        raise(SignalKind::USER_DEFINED1);
+        for _ in 0..1000 {
+            executor.poll();
+            if let Ok(result) = rx.try_recv() {
+                assert_eq!(result, "received");
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Signal was not delivered");
+    }
+
+    #[test]
+    fn test_signal_stream_receives_repeatedly() {
+        let mut executor = Executor::new();
+        let mut user2 = signal(SignalKind::USER_DEFINED2).unwrap();
+        let (tx, progress) = std::sync::mpsc::channel();
+        let rx = executor.spawn(async move {
+            for i in 0..3 {
+                user2.recv().await;
+                let _ = tx.send(i);
+            }
+        });
+
+        for expected in 0..3 {
+            raise(SignalKind::USER_DEFINED2);
+            let mut seen = false;
+            for _ in 0..1000 {
+                executor.poll();
+                if let Ok(i) = progress.try_recv() {
+                    assert_eq!(i, expected);
+                    seen = true;
+                    break;
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+            assert!(seen, "Delivery {} was not observed", expected);
+        }
+        executor.poll();
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_multiple_listeners_each_receive() {
+        let mut executor = Executor::new();
+        let mut receivers = Vec::new();
+        for _ in 0..2 {
+            let mut winch = signal(SignalKind::WINDOW_CHANGE).unwrap();
+            receivers.push(executor.spawn(async move { winch.recv().await }));
+        }
+
+        raise(SignalKind::WINDOW_CHANGE);
+        let mut received = 0;
+        for _ in 0..1000 {
+            executor.poll();
+            received += receivers.iter().filter(|rx| rx.try_recv().is_ok()).count();
+            if received == 2 {
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Only {} of 2 listeners were woken", received);
+    }
+
+    #[test]
+    fn test_forbidden_signals_are_rejected() {
+        for signum in [9, 11] {
+            let result = signal(SignalKind::from_raw(signum));
+            assert_eq!(result.err().unwrap().kind(), std::io::ErrorKind::InvalidInput);
+        }
+        assert!(signal(SignalKind::from_raw(0)).is_err());
+        assert!(signal(SignalKind::from_raw(1000)).is_err());
+    }
+}
@@ -1,5 +1,9 @@
+use crate::runtime::builder;
+use crate::runtime::clock::Clock;
+use crate::runtime::timer::TimerDriver;
 use std::{
     future::Future,
+    io,
     pin::Pin,
     task::{Context, Poll},
     time::{Duration, Instant},
@@ -7,24 +11,87 @@ use std::{
 
 pub struct Sleep {
     when: Instant,
+    clock: Clock,
+    timer: Option<u64>,
+    // The runtime's timer thread, once the sleep has registered with it.
+    driver: Option<TimerDriver>,
 }
 impl Sleep {
     pub fn new(duration: Duration) -> Self {
+        let clock = Clock::current();
         Sleep {
-            when: Instant::now() + duration,
+            when: clock.now() + duration,
+            clock,
+            timer: None,
+            driver: None,
         }
     }
 }
 
 impl Future for Sleep {
     type Output = ();
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let now = Instant::now();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let now = self.clock.now();
         if now >= self.when {
-            Poll::Ready(())
-        } else {
+            return Poll::Ready(());
+        }
+        let when = self.when;
+        if self.clock.is_simulated() {
+            // Virtual time only moves when the clock is advanced, which wakes us.
+            self.timer = self.clock.register_timer(self.timer, when, cx.waker());
+            return Poll::Pending;
+        }
+        if self.driver.is_none() {
+            self.driver = builder::timer_driver();
+        }
+        self.timer = match &self.driver {
+            Some(driver) => driver.register(self.timer, when, cx.waker()),
+            None => None,
+        };
+        if self.timer.is_none() {
+            // No timer thread will wake us, so check again on the next poll.
             cx.waker().wake_by_ref();
-            Poll::Pending
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer {
+            match &self.driver {
+                Some(driver) => driver.cancel(timer),
+                None => self.clock.cancel_timer(timer),
+            }
+        }
+    }
+}
+
+/// Runs `future`, failing with `ErrorKind::TimedOut` if it has not finished after `duration`.
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    Timeout {
+        future: Box::pin(future),
+        sleep: Sleep::new(duration),
+    }
+}
+
+pub struct Timeout<F: Future> {
+    future: Pin<Box<F>>,
+    sleep: Sleep,
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = io::Result<F::Output>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        match Pin::new(&mut self.sleep).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "future timed out",
+            ))),
+            Poll::Pending => Poll::Pending,
         }
     }
 }
@@ -76,4 +76,52 @@ mod tests {
         
         // Just verify both can be created - we can't access private fields
     }
+
+    #[test]
+    fn test_timeout_expires_on_simulated_clock() {
+        use crate::runtime::clock::Clock;
+        use crate::runtime::executor::Executor;
+        use crate::runtime::sleep::timeout;
+
+        let mut executor = Executor::with_clock(Clock::simulated());
+        let rx = executor.spawn(async {
+            timeout(Duration::from_secs(5), Sleep::new(Duration::from_secs(60))).await
+        });
+
+        for _ in 0..10 {
+            executor.poll();
+            if let Ok(result) = rx.try_recv() {
+                assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::TimedOut);
+                return;
+            }
+        }
+        panic!("Timeout did not fire");
+    }
+
+    #[test]
+    fn test_timeout_returns_output_when_future_finishes_first() {
+        use crate::runtime::clock::Clock;
+        use crate::runtime::executor::Executor;
+        use crate::runtime::sleep::timeout;
+
+        let mut executor = Executor::with_clock(Clock::simulated());
+        let rx = executor.spawn(async {
+            timeout(Duration::from_secs(5), async {
+                Sleep::new(Duration::from_secs(1)).await;
+                7
+            })
+            .await
+        });
+
+        for _ in 0..10 {
+            executor.poll();
+            if let Ok(result) = rx.try_recv() {
+                assert_eq!(result.unwrap(), 7);
+                // The losing timer must not linger on the clock.
+                assert_eq!(executor.clock().next_deadline(), None);
+                return;
+            }
+        }
+        panic!("Future did not complete within the timeout");
+    }
 }
@@ -0,0 +1,202 @@
+//! The handful of libc calls the runtime needs that std does not expose.
+
+use std::{
+    io,
+    os::fd::{AsRawFd, OwnedFd, RawFd},
+    os::raw::{c_int, c_short},
+};
+
+#[cfg(target_os = "linux")]
+type NfdsT = std::os::raw::c_ulong;
+#[cfg(not(target_os = "linux"))]
+type NfdsT = std::os::raw::c_uint;
+
+const F_GETFL: c_int = 3;
+const F_SETFL: c_int = 4;
+#[cfg(target_os = "linux")]
+const O_NONBLOCK: c_int = 0o4000;
+#[cfg(not(target_os = "linux"))]
+const O_NONBLOCK: c_int = 0x0004;
+
+pub const POLLIN: c_short = 0x001;
+pub const POLLERR: c_short = 0x008;
+pub const POLLHUP: c_short = 0x010;
+pub const POLLNVAL: c_short = 0x020;
+
+#[cfg(target_os = "linux")]
+const SYS_PIDFD_OPEN: std::os::raw::c_long = 434;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct PollFd {
+    pub fd: c_int,
+    pub events: c_short,
+    pub revents: c_short,
+}
+
+impl PollFd {
+    pub fn new(fd: RawFd, events: c_short) -> Self {
+        PollFd {
+            fd,
+            events,
+            revents: 0,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.revents & (self.events | POLLERR | POLLHUP | POLLNVAL) != 0
+    }
+}
+
+// `sighandler_t` is a plain function pointer, carried as an integer across the FFI.
+type SigHandler = usize;
+
+#[cfg(target_os = "linux")]
+const SA_RESTART: c_int = 0x1000_0000;
+#[cfg(not(target_os = "linux"))]
+const SA_RESTART: c_int = 0x0002;
+
+// glibc and musl both use a 1024-bit signal set and append the restorer, which libc fills in.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+struct SigAction {
+    sa_handler: SigHandler,
+    sa_mask: [u64; 16],
+    sa_flags: c_int,
+    sa_restorer: usize,
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[repr(C)]
+struct SigAction {
+    sa_handler: SigHandler,
+    sa_mask: u32,
+    sa_flags: c_int,
+}
+
+// FreeBSD puts the flags first and uses a 128-bit signal set.
+#[cfg(target_os = "freebsd")]
+#[repr(C)]
+struct SigAction {
+    sa_handler: SigHandler,
+    sa_flags: c_int,
+    sa_mask: [u32; 4],
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd"
+)))]
+compile_error!("the `sigaction` layout is only known for Linux, macOS, iOS and FreeBSD");
+
+unsafe extern "C" {
+    fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+    fn sigaction(signum: c_int, act: *const SigAction, oldact: *mut SigAction) -> c_int;
+    #[cfg(target_os = "linux")]
+    fn __errno_location() -> *mut c_int;
+    #[cfg(not(target_os = "linux"))]
+    fn __error() -> *mut c_int;
+    fn write(fd: c_int, buf: *const u8, count: usize) -> isize;
+    #[link_name = "poll"]
+    fn libc_poll(fds: *mut PollFd, nfds: NfdsT, timeout: c_int) -> c_int;
+    #[cfg(target_os = "linux")]
+    fn syscall(number: std::os::raw::c_long, ...) -> std::os::raw::c_long;
+}
+
+fn cvt(result: c_int) -> io::Result<c_int> {
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result)
+    }
+}
+
+pub fn set_nonblocking(fd: &impl AsRawFd, nonblocking: bool) -> io::Result<()> {
+    let fd = fd.as_raw_fd();
+    // SAFETY: F_GETFL and F_SETFL only read and update the flags of an fd we borrow.
+    unsafe {
+        let flags = cvt(fcntl(fd, F_GETFL))?;
+        let flags = if nonblocking {
+            flags | O_NONBLOCK
+        } else {
+            flags & !O_NONBLOCK
+        };
+        cvt(fcntl(fd, F_SETFL, flags))?;
+    }
+    Ok(())
+}
+
+/// Waits until one of `fds` is ready. A negative timeout waits forever.
+pub fn poll(fds: &mut [PollFd], timeout_ms: c_int) -> io::Result<usize> {
+    loop {
+        // SAFETY: the pointer and length come from a live mutable slice of `PollFd`s.
+        let result = unsafe { libc_poll(fds.as_mut_ptr(), fds.len() as NfdsT, timeout_ms) };
+        match cvt(result) {
+            Ok(n) => return Ok(n as usize),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Installs `handler` for `signum`. The handler stays installed, blocks no other signals,
+/// and syscalls it interrupts are restarted.
+pub fn install_signal_handler(signum: c_int, handler: extern "C" fn(c_int)) -> io::Result<()> {
+    // SAFETY: an all-zero `sigaction` is valid: an empty mask and no flags.
+    let mut action: SigAction = unsafe { std::mem::zeroed() };
+    action.sa_handler = handler as SigHandler;
+    action.sa_flags = SA_RESTART;
+    // SAFETY: `action` is fully initialized and the handler is a valid
+    // `extern "C" fn(c_int)` for the life of the program.
+    cvt(unsafe { sigaction(signum, &action, std::ptr::null_mut()) })?;
+    Ok(())
+}
+
+fn errno_location() -> *mut c_int {
+    // SAFETY: both return the calling thread's errno, which lives as long as the thread.
+    #[cfg(target_os = "linux")]
+    unsafe {
+        __errno_location()
+    }
+    #[cfg(not(target_os = "linux"))]
+    unsafe {
+        __error()
+    }
+}
+
+/// The calling thread's `errno`, for a signal handler to restore before it returns.
+pub fn errno() -> c_int {
+    // SAFETY: the pointer is valid for the calling thread.
+    unsafe { *errno_location() }
+}
+
+pub fn set_errno(value: c_int) {
+    // SAFETY: the pointer is valid for the calling thread.
+    unsafe { *errno_location() = value }
+}
+
+/// Writes a single byte with no allocation or locking, so it is safe in a signal handler.
+pub fn write_byte(fd: RawFd, byte: u8) {
+    // SAFETY: `write` is async-signal-safe and reads exactly one byte from the stack.
+    unsafe {
+        write(fd, &byte, 1);
+    }
+}
+
+/// Opens a pidfd that becomes readable once the process exits.
+#[cfg(target_os = "linux")]
+pub fn pidfd_open(pid: u32) -> io::Result<OwnedFd> {
+    use std::os::fd::FromRawFd;
+    // SAFETY: pidfd_open takes a pid and flags and returns a new fd or -1.
+    let fd = unsafe { syscall(SYS_PIDFD_OPEN, pid as c_int, 0 as c_int) };
+    let fd = cvt(fd as c_int)?;
+    // SAFETY: the kernel just handed us ownership of this fd.
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pidfd_open(_pid: u32) -> io::Result<OwnedFd> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
@@ -0,0 +1,136 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    thread,
+};
+
+/// Declares task-local keys of type [`LocalKey`].
+///
+/// ```
+/// std_async::task_local! {
+///     static REQUEST_ID: u64;
+/// }
+/// ```
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty; $($rest:tt)*) => {
+        $(#[$attr])*
+        $vis static $name: $crate::runtime::task_local::LocalKey<$t> = {
+            ::std::thread_local! {
+                static __KEY: ::std::cell::RefCell<::std::option::Option<$t>> =
+                    const { ::std::cell::RefCell::new(::std::option::Option::None) };
+            }
+            $crate::runtime::task_local::LocalKey { inner: __KEY }
+        };
+        $crate::task_local!($($rest)*);
+    };
+}
+
+/// A value that is set for the duration of a future with [`LocalKey::scope`] and read
+/// from anywhere inside it with [`LocalKey::with`].
+///
+/// The scope swaps the value into thread-local storage around every poll of the future,
+/// so it follows the task no matter which executor or thread polls it.
+pub struct LocalKey<T: 'static> {
+    #[doc(hidden)]
+    pub inner: thread::LocalKey<RefCell<Option<T>>>,
+}
+
+impl<T: 'static> LocalKey<T> {
+    /// Runs `future` with the key set to `value`.
+    pub fn scope<F: Future>(&'static self, value: T, future: F) -> TaskLocalFuture<T, F> {
+        TaskLocalFuture {
+            key: self,
+            slot: Some(value),
+            future: Box::pin(future),
+        }
+    }
+
+    /// Runs `f` with the key set to `value`, for synchronous code.
+    pub fn sync_scope<F: FnOnce() -> R, R>(&'static self, value: T, f: F) -> R {
+        let mut slot = Some(value);
+        self.enter(&mut slot, f)
+    }
+
+    /// Calls `f` with the current value. Panics outside of a scope.
+    pub fn with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> R {
+        match self.try_with(f) {
+            Ok(result) => result,
+            Err(_) => panic!("cannot access a task-local value outside of LocalKey::scope"),
+        }
+    }
+
+    pub fn try_with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> Result<R, AccessError> {
+        self.inner.with(|cell| match cell.borrow().as_ref() {
+            Some(value) => Ok(f(value)),
+            None => Err(AccessError),
+        })
+    }
+
+    pub fn get(&'static self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+
+    // Moves the value out of `slot` for the duration of `f` and back afterwards, even if
+    // `f` panics, restoring whatever an enclosing scope had set.
+    fn enter<F: FnOnce() -> R, R>(&'static self, slot: &mut Option<T>, f: F) -> R {
+        struct Guard<'a, T: 'static> {
+            key: &'static LocalKey<T>,
+            slot: &'a mut Option<T>,
+        }
+        impl<T: 'static> Drop for Guard<'_, T> {
+            fn drop(&mut self) {
+                self.key.inner.with(|cell| {
+                    std::mem::swap(self.slot, &mut *cell.borrow_mut());
+                });
+            }
+        }
+        self.inner.with(|cell| {
+            let mut current = cell
+                .try_borrow_mut()
+                .expect("cannot enter a task-local scope while the value is borrowed");
+            std::mem::swap(slot, &mut *current);
+        });
+        let _guard = Guard { key: self, slot };
+        f()
+    }
+}
+
+/// Future returned by [`LocalKey::scope`].
+pub struct TaskLocalFuture<T: 'static, F: Future> {
+    key: &'static LocalKey<T>,
+    slot: Option<T>,
+    future: Pin<Box<F>>,
+}
+
+// The value is only ever moved in and out of the slot, never pinned.
+impl<T: 'static, F: Future> Unpin for TaskLocalFuture<T, F> {}
+
+impl<T: 'static, F: Future> Future for TaskLocalFuture<T, F> {
+    type Output = F::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let future = &mut this.future;
+        this.key.enter(&mut this.slot, || future.as_mut().poll(cx))
+    }
+}
+
+/// Returned by [`LocalKey::try_with`] outside of a scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessError;
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "task-local value accessed outside of its scope")
+    }
+}
+
+impl Error for AccessError {}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::executor::Executor;
+    use crate::runtime::task_local::AccessError;
+    use crate::runtime::yield_now;
+
+    crate::task_local! {
+        static REQUEST_ID: u64;
+        static PEER: String;
+    }
+
+    #[test]
+    fn test_value_follows_the_task_across_polls() {
+        let mut executor = Executor::new();
+        let mut results = Vec::new();
+        for id in 0..3 {
+            results.push(executor.spawn(REQUEST_ID.scope(id, async move {
+                let mut seen = Vec::new();
+                for _ in 0..3 {
+                    seen.push(REQUEST_ID.get());
+                    yield_now().await;
+                }
+                seen
+            })));
+        }
+        executor.run();
+        for (id, rx) in results.into_iter().enumerate() {
+            assert_eq!(rx.recv().unwrap(), vec![id as u64; 3]);
+        }
+    }
+
+    #[test]
+    fn test_not_set_outside_of_scope() {
+        assert_eq!(REQUEST_ID.try_with(|id| *id), Err(AccessError));
+        let mut executor = Executor::new();
+        let rx = executor.spawn(async { REQUEST_ID.try_with(|id| *id) });
+        executor.run();
+        assert_eq!(rx.recv().unwrap(), Err(AccessError));
+    }
+
+    #[test]
+    fn test_nested_scopes_restore_outer_value() {
+        let mut executor = Executor::new();
+        let rx = executor.spawn(PEER.scope(String::from("outer"), async {
+            let inner = PEER
+                .scope(String::from("inner"), async {
+                    yield_now().await;
+                    PEER.get()
+                })
+                .await;
+            (inner, PEER.get())
+        }));
+        executor.run();
+        assert_eq!(
+            rx.recv().unwrap(),
+            (String::from("inner"), String::from("outer"))
+        );
+    }
+
+    #[test]
+    fn test_sync_scope() {
+        let length = PEER.sync_scope(String::from("peer"), || PEER.with(|peer| peer.len()));
+        assert_eq!(length, 4);
+        assert!(PEER.try_with(|_| ()).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of LocalKey::scope")]
+    fn test_with_panics_outside_of_scope() {
+        REQUEST_ID.with(|_| ());
+    }
+}
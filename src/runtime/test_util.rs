@@ -0,0 +1,26 @@
+use crate::runtime::executor::Executor;
+use std::{
+    future::Future,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Drives `future` on a fresh executor until it finishes, panicking if that takes more
+/// than a few seconds.
+pub(crate) fn run<F>(future: F) -> F::Output
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let mut executor = Executor::new();
+    let rx = executor.spawn(future);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        executor.poll();
+        if let Ok(result) = rx.try_recv() {
+            return result;
+        }
+        thread::sleep(Duration::from_micros(100));
+    }
+    panic!("Future did not complete");
+}
@@ -0,0 +1,122 @@
+use std::{
+    io,
+    sync::{Arc, Condvar, Mutex},
+    task::Waker,
+    thread,
+    time::Instant,
+};
+
+/// A thread that wakes sleeps when their deadline passes, so that a runtime built with
+/// [`Builder::enable_time`](crate::runtime::Builder::enable_time) doesn't have to keep
+/// polling them. Clones share the same thread.
+#[derive(Clone)]
+pub(crate) struct TimerDriver {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    timers: Vec<Timer>,
+    shutdown: bool,
+}
+
+struct Timer {
+    id: u64,
+    when: Instant,
+    waker: Waker,
+}
+
+impl TimerDriver {
+    /// Starts the driver thread. It runs until [`TimerDriver::shutdown`].
+    pub(crate) fn spawn(name: String) -> io::Result<TimerDriver> {
+        let driver = TimerDriver {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State::default()),
+                condvar: Condvar::new(),
+            }),
+        };
+        let shared = driver.shared.clone();
+        thread::Builder::new().name(name).spawn(move || shared.run())?;
+        Ok(driver)
+    }
+
+    /// Registers or refreshes a timer, returning its id. Returns `None` once the driver
+    /// has shut down, in which case nothing will wake `waker`.
+    pub(crate) fn register(&self, id: Option<u64>, when: Instant, waker: &Waker) -> Option<u64> {
+        let mut state = self.shared.state.lock().unwrap();
+        if state.shutdown {
+            return None;
+        }
+        let id = match id.and_then(|id| state.timers.iter_mut().find(|timer| timer.id == id)) {
+            Some(timer) => {
+                timer.when = when;
+                timer.waker.clone_from(waker);
+                timer.id
+            }
+            None => {
+                let id = state.next_id;
+                state.next_id += 1;
+                state.timers.push(Timer {
+                    id,
+                    when,
+                    waker: waker.clone(),
+                });
+                id
+            }
+        };
+        drop(state);
+        self.shared.condvar.notify_one();
+        Some(id)
+    }
+
+    pub(crate) fn cancel(&self, id: u64) {
+        self.shared.state.lock().unwrap().timers.retain(|timer| timer.id != id);
+    }
+
+    /// Stops the thread and wakes every pending timer so that nothing waits on it forever.
+    pub(crate) fn shutdown(&self) {
+        let timers = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.shutdown = true;
+            std::mem::take(&mut state.timers)
+        };
+        self.shared.condvar.notify_one();
+        for timer in timers {
+            timer.waker.wake();
+        }
+    }
+}
+
+impl Shared {
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+        while !state.shutdown {
+            let now = Instant::now();
+            let mut due = Vec::new();
+            let mut index = 0;
+            while index < state.timers.len() {
+                if state.timers[index].when <= now {
+                    due.push(state.timers.swap_remove(index).waker);
+                } else {
+                    index += 1;
+                }
+            }
+            if !due.is_empty() {
+                drop(state);
+                due.into_iter().for_each(Waker::wake);
+                state = self.state.lock().unwrap();
+                continue;
+            }
+            state = match state.timers.iter().map(|timer| timer.when).min() {
+                Some(next) => self.condvar.wait_timeout(state, next - now).unwrap().0,
+                None => self.condvar.wait(state).unwrap(),
+            };
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::runtime::io::poll_would_block;
+use std::{
+    future, io,
+    net::{self, SocketAddr, ToSocketAddrs},
+};
+
+/// A UDP socket whose sends and receives wait for readiness instead of blocking.
+pub struct UdpSocket {
+    socket: net::UdpSocket,
+}
+
+impl UdpSocket {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<UdpSocket> {
+        UdpSocket::from_std(net::UdpSocket::bind(addr)?)
+    }
+
+    pub fn from_std(socket: net::UdpSocket) -> io::Result<UdpSocket> {
+        socket.set_nonblocking(true)?;
+        Ok(UdpSocket { socket })
+    }
+
+    pub fn into_std(self) -> io::Result<net::UdpSocket> {
+        self.socket.set_nonblocking(false)?;
+        Ok(self.socket)
+    }
+
+    /// Sets the default peer for `send`/`recv` and filters incoming datagrams to it. Like
+    /// `send_to`, it takes a resolved address so that no lookup blocks the worker.
+    pub fn connect(&self, addr: SocketAddr) -> io::Result<()> {
+        self.socket.connect(addr)
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.peer_addr()
+    }
+
+    pub fn set_broadcast(&self, broadcast: bool) -> io::Result<()> {
+        self.socket.set_broadcast(broadcast)
+    }
+
+    /// Takes a resolved address, since looking up a host name would block the worker.
+    pub async fn send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        future::poll_fn(|cx| poll_would_block(cx, || self.socket.send_to(buf, target))).await
+    }
+
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        future::poll_fn(|cx| poll_would_block(cx, || self.socket.recv_from(buf))).await
+    }
+
+    pub async fn peek_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        future::poll_fn(|cx| poll_would_block(cx, || self.socket.peek_from(buf))).await
+    }
+
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        future::poll_fn(|cx| poll_would_block(cx, || self.socket.send(buf))).await
+    }
+
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        future::poll_fn(|cx| poll_would_block(cx, || self.socket.recv(buf))).await
+    }
+
+    pub async fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
+        future::poll_fn(|cx| poll_would_block(cx, || self.socket.peek(buf))).await
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::executor::Executor;
+    use crate::runtime::test_util::run;
+    use crate::runtime::udp::UdpSocket;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_udp_bind() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        assert!(addr.ip().is_loopback());
+        assert_ne!(addr.port(), 0);
+    }
+
+    #[test]
+    fn test_udp_send_to_recv_from() {
+        let result = run(async {
+            let server = UdpSocket::bind("127.0.0.1:0")?;
+            let client = UdpSocket::bind("127.0.0.1:0")?;
+            let server_addr = server.local_addr()?;
+
+            client.send_to(b"ping", server_addr).await?;
+            let mut buf = [0u8; 16];
+            let (n, from) = server.recv_from(&mut buf).await?;
+            assert_eq!(&buf[..n], b"ping");
+            assert_eq!(from, client.local_addr()?);
+
+            server.send_to(b"pong", from).await?;
+            let (n, _) = client.recv_from(&mut buf).await?;
+            Ok::<_, std::io::Error>(buf[..n].to_vec())
+        });
+        assert_eq!(result.unwrap(), b"pong");
+    }
+
+    #[test]
+    fn test_udp_connected_send_recv() {
+        let result = run(async {
+            let a = UdpSocket::bind("127.0.0.1:0")?;
+            let b = UdpSocket::bind("127.0.0.1:0")?;
+            a.connect(b.local_addr()?)?;
+            b.connect(a.local_addr()?)?;
+            assert_eq!(a.peer_addr()?, b.local_addr()?);
+
+            a.send(b"connected").await?;
+            let mut buf = [0u8; 16];
+            let n = b.recv(&mut buf).await?;
+            Ok::<_, std::io::Error>(buf[..n].to_vec())
+        });
+        assert_eq!(result.unwrap(), b"connected");
+    }
+
+    #[test]
+    fn test_udp_peek_does_not_consume() {
+        let result = run(async {
+            let a = UdpSocket::bind("127.0.0.1:0")?;
+            let b = UdpSocket::bind("127.0.0.1:0")?;
+            a.send_to(b"datagram", b.local_addr()?).await?;
+
+            let mut buf = [0u8; 16];
+            let (peeked, from) = b.peek_from(&mut buf).await?;
+            assert_eq!(from, a.local_addr()?);
+            b.connect(from)?;
+            assert_eq!(b.peek(&mut buf).await?, peeked);
+            let n = b.recv(&mut buf).await?;
+            assert_eq!(n, peeked);
+            Ok::<_, std::io::Error>(buf[..n].to_vec())
+        });
+        assert_eq!(result.unwrap(), b"datagram");
+    }
+
+    #[test]
+    fn test_udp_recv_waits_without_blocking_executor() {
+        let receiver = Arc::new(UdpSocket::bind("127.0.0.1:0").unwrap());
+        let addr = receiver.local_addr().unwrap();
+        let mut executor = Executor::new();
+
+        let waiting = executor.spawn(async move {
+            let mut buf = [0u8; 16];
+            receiver.recv(&mut buf).await.map(|n| buf[..n].to_vec())
+        });
+        let other = executor.spawn(async { "other task ran" });
+
+        for _ in 0..5 {
+            executor.poll();
+        }
+        assert_eq!(other.try_recv().unwrap(), "other task ran");
+        assert!(waiting.try_recv().is_err());
+
+        let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        sender.send_to(b"late", addr).unwrap();
+        for _ in 0..1000 {
+            executor.poll();
+            if let Ok(result) = waiting.try_recv() {
+                assert_eq!(result.unwrap(), b"late");
+                return;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Datagram was not received");
+    }
+}
@@ -0,0 +1,226 @@
+use crate::runtime::blocking::spawn_blocking;
+use crate::runtime::io::{AsyncRead, AsyncWrite, poll_would_block};
+use crate::runtime::reciever::StreamReceiver;
+use crate::runtime::sender::StreamSender;
+use std::{
+    future,
+    io::{self, Read, Write},
+    net::Shutdown,
+    os::fd::{AsRawFd, RawFd},
+    os::unix::net::{self, SocketAddr},
+    path::Path,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// Unix socket counterpart of [`TcpSender`](crate::runtime::sender::TcpSender).
+pub type UnixSender = StreamSender<net::UnixStream>;
+
+/// Unix socket counterpart of [`TcpReceiver`](crate::runtime::reciever::TcpReceiver).
+pub type UnixReceiver = StreamReceiver<net::UnixStream>;
+
+pub struct UnixListener {
+    listener: net::UnixListener,
+}
+
+impl UnixListener {
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<UnixListener> {
+        UnixListener::from_std(net::UnixListener::bind(path)?)
+    }
+
+    pub fn from_std(listener: net::UnixListener) -> io::Result<UnixListener> {
+        listener.set_nonblocking(true)?;
+        Ok(UnixListener { listener })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub async fn accept(&self) -> io::Result<(UnixStream, SocketAddr)> {
+        let (stream, addr) =
+            future::poll_fn(|cx| poll_would_block(cx, || self.listener.accept())).await?;
+        Ok((UnixStream::from_std(stream)?, addr))
+    }
+}
+
+impl AsRawFd for UnixListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.listener.as_raw_fd()
+    }
+}
+
+/// A Unix stream socket implementing the runtime's [`AsyncRead`] and [`AsyncWrite`].
+pub struct UnixStream {
+    stream: Arc<net::UnixStream>,
+}
+
+impl UnixStream {
+    pub async fn connect(path: impl AsRef<Path>) -> io::Result<UnixStream> {
+        let path = path.as_ref().to_owned();
+        let stream = spawn_blocking(move || net::UnixStream::connect(path)).await??;
+        UnixStream::from_std(stream)
+    }
+
+    pub fn pair() -> io::Result<(UnixStream, UnixStream)> {
+        let (a, b) = net::UnixStream::pair()?;
+        Ok((UnixStream::from_std(a)?, UnixStream::from_std(b)?))
+    }
+
+    pub fn from_std(stream: net::UnixStream) -> io::Result<UnixStream> {
+        stream.set_nonblocking(true)?;
+        Ok(UnixStream {
+            stream: Arc::new(stream),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.local_addr()
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    /// Splits the stream into halves that can be moved into separate tasks.
+    pub fn split(self) -> (ReadHalf, WriteHalf) {
+        (
+            ReadHalf {
+                stream: self.stream.clone(),
+            },
+            WriteHalf {
+                stream: self.stream,
+            },
+        )
+    }
+}
+
+pub struct ReadHalf {
+    stream: Arc<net::UnixStream>,
+}
+
+pub struct WriteHalf {
+    stream: Arc<net::UnixStream>,
+}
+
+fn poll_read(
+    stream: &net::UnixStream,
+    cx: &mut Context<'_>,
+    buf: &mut [u8],
+) -> Poll<io::Result<usize>> {
+    poll_would_block(cx, || (&*stream).read(buf))
+}
+
+fn poll_write(stream: &net::UnixStream, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+    poll_would_block(cx, || (&*stream).write(buf))
+}
+
+impl AsyncRead for UnixStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        poll_read(&self.stream, cx, buf)
+    }
+}
+
+impl AsyncWrite for UnixStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        poll_write(&self.stream, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.stream.shutdown(Shutdown::Write))
+    }
+}
+
+impl AsyncRead for ReadHalf {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        poll_read(&self.stream, cx, buf)
+    }
+}
+
+impl AsyncWrite for WriteHalf {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        poll_write(&self.stream, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(self.stream.shutdown(Shutdown::Write))
+    }
+}
+
+pub struct UnixDatagram {
+    socket: net::UnixDatagram,
+}
+
+impl UnixDatagram {
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<UnixDatagram> {
+        UnixDatagram::from_std(net::UnixDatagram::bind(path)?)
+    }
+
+    pub fn unbound() -> io::Result<UnixDatagram> {
+        UnixDatagram::from_std(net::UnixDatagram::unbound()?)
+    }
+
+    pub fn pair() -> io::Result<(UnixDatagram, UnixDatagram)> {
+        let (a, b) = net::UnixDatagram::pair()?;
+        Ok((UnixDatagram::from_std(a)?, UnixDatagram::from_std(b)?))
+    }
+
+    pub fn from_std(socket: net::UnixDatagram) -> io::Result<UnixDatagram> {
+        socket.set_nonblocking(true)?;
+        Ok(UnixDatagram { socket })
+    }
+
+    pub fn connect(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.socket.connect(path)
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.peer_addr()
+    }
+
+    pub async fn send_to(&self, buf: &[u8], path: impl AsRef<Path>) -> io::Result<usize> {
+        let path = path.as_ref();
+        future::poll_fn(|cx| poll_would_block(cx, || self.socket.send_to(buf, path))).await
+    }
+
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        future::poll_fn(|cx| poll_would_block(cx, || self.socket.recv_from(buf))).await
+    }
+
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        future::poll_fn(|cx| poll_would_block(cx, || self.socket.send(buf))).await
+    }
+
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        future::poll_fn(|cx| poll_would_block(cx, || self.socket.recv(buf))).await
+    }
+}
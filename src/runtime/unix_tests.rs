@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::executor::Executor;
+    use crate::runtime::io::{AsyncReadExt, AsyncWriteExt};
+    use crate::runtime::test_util::run;
+    use crate::runtime::unix::{UnixDatagram, UnixListener, UnixReceiver, UnixSender, UnixStream};
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    fn socket_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("std_async_{}_{}.sock", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_unix_sender_and_receiver() {
+        let (ours, mut theirs) = std::os::unix::net::UnixStream::pair().unwrap();
+        let peer = thread::spawn(move || {
+            use std::io::{Read, Write};
+            let mut received = Vec::new();
+            theirs.read_to_end(&mut received).unwrap();
+            theirs.write_all(b"ack").unwrap();
+            received
+        });
+
+        let stream = Arc::new(Mutex::new(ours));
+        let response = run(async move {
+            UnixSender {
+                stream: stream.clone(),
+                buffer: b"over a unix socket".to_vec(),
+            }
+            .await?;
+            UnixReceiver {
+                stream,
+                buffer: Vec::new(),
+            }
+            .await
+        });
+
+        assert_eq!(peer.join().unwrap(), b"over a unix socket");
+        assert_eq!(response.unwrap(), b"ack");
+    }
+
+    #[test]
+    fn test_listener_accept_and_connect() {
+        let path = socket_path("listener");
+        let listener = UnixListener::bind(&path).unwrap();
+        let mut executor = Executor::new();
+
+        let server = executor.spawn(async move {
+            let (mut stream, _) = listener.accept().await?;
+            let mut request = String::new();
+            stream.read_to_string(&mut request).await?;
+            stream.write_all(request.to_uppercase().as_bytes()).await?;
+            stream.shutdown().await
+        });
+        let client = executor.spawn({
+            let path = path.clone();
+            async move {
+                let mut stream = UnixStream::connect(&path).await?;
+                stream.write_all(b"hello").await?;
+                stream.shutdown().await?;
+                let mut response = String::new();
+                stream.read_to_string(&mut response).await?;
+                Ok::<_, std::io::Error>(response)
+            }
+        });
+
+        let mut response = None;
+        for _ in 0..5000 {
+            executor.poll();
+            if let Ok(result) = client.try_recv() {
+                response = Some(result.unwrap());
+                break;
+            }
+            thread::sleep(Duration::from_micros(100));
+        }
+        assert_eq!(response.as_deref(), Some("HELLO"));
+        for _ in 0..100 {
+            executor.poll();
+        }
+        assert!(server.try_recv().unwrap().is_ok());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_stream_split_halves() {
+        let (a, b) = UnixStream::pair().unwrap();
+        let (mut a_read, mut a_write) = a.split();
+        let (mut b_read, mut b_write) = b.split();
+        let mut executor = Executor::new();
+
+        let writer = executor.spawn(async move {
+            a_write.write_all(b"from a").await?;
+            a_write.shutdown().await
+        });
+        let echo = executor.spawn(async move {
+            let mut buf = Vec::new();
+            b_read.read_to_end(&mut buf).await?;
+            b_write.write_all(&buf).await?;
+            b_write.shutdown().await
+        });
+        let reader = executor.spawn(async move {
+            let mut buf = Vec::new();
+            a_read.read_to_end(&mut buf).await.map(|_| buf)
+        });
+
+        for _ in 0..5000 {
+            executor.poll();
+            if let Ok(result) = reader.try_recv() {
+                assert_eq!(result.unwrap(), b"from a");
+                assert!(writer.try_recv().unwrap().is_ok());
+                assert!(echo.try_recv().unwrap().is_ok());
+                return;
+            }
+        }
+        panic!("Split halves did not exchange data");
+    }
+
+    #[test]
+    fn test_datagram_pair() {
+        let result = run(async {
+            let (a, b) = UnixDatagram::pair()?;
+            a.send(b"one").await?;
+            a.send(b"two").await?;
+            let mut buf = [0u8; 8];
+            let n = b.recv(&mut buf).await?;
+            assert_eq!(&buf[..n], b"one");
+            let n = b.recv(&mut buf).await?;
+            Ok::<_, std::io::Error>(buf[..n].to_vec())
+        });
+        assert_eq!(result.unwrap(), b"two");
+    }
+
+    #[test]
+    fn test_datagram_send_to_path() {
+        let path = socket_path("datagram");
+        let result = run({
+            let path = path.clone();
+            async move {
+                let server = UnixDatagram::bind(&path)?;
+                let client = UnixDatagram::unbound()?;
+                client.send_to(b"telemetry", &path).await?;
+                let mut buf = [0u8; 16];
+                let (n, _) = server.recv_from(&mut buf).await?;
+                Ok::<_, std::io::Error>(buf[..n].to_vec())
+            }
+        });
+        assert_eq!(result.unwrap(), b"telemetry");
+        let _ = std::fs::remove_file(path);
+    }
+}
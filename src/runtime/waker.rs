@@ -1,4 +1,11 @@
-use std::task::{RawWaker, RawWakerVTable};
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{RawWaker, RawWakerVTable, Wake},
+    thread::Thread,
+};
 
 static VTABLE: RawWakerVTable = RawWakerVTable::new(my_clone, my_wake, my_wake_by_ref, my_drop);
 
@@ -28,3 +35,68 @@ pub fn create_raw_waker() -> RawWaker {
     let data = Box::into_raw(Box::new(42u32));
     RawWaker::new(data as *const (), &VTABLE)
 }
+
+/// The thread an executor is parked on, if any. Shared with the wakers of its tasks, so
+/// that waking a task unparks the thread.
+#[derive(Clone, Default)]
+pub(crate) struct ParkedThread(Arc<Mutex<Option<Thread>>>);
+
+impl ParkedThread {
+    pub(crate) fn set(&self, thread: Option<Thread>) {
+        *self.0.lock().unwrap() = thread;
+    }
+
+    fn unpark(&self) {
+        if let Some(thread) = &*self.0.lock().unwrap() {
+            thread.unpark();
+        }
+    }
+}
+
+/// Waker handed to tasks by the executor. It records whether the task asked to be polled
+/// again, which lets the executor tell idle tasks from busy ones.
+pub struct TaskWaker {
+    woken: AtomicBool,
+    thread: ParkedThread,
+}
+
+impl TaskWaker {
+    pub fn new() -> Arc<TaskWaker> {
+        TaskWaker::parking(ParkedThread::default())
+    }
+
+    /// A waker that also unparks `thread`, for a thread that parks until it is woken.
+    pub fn unparking(thread: Thread) -> Arc<TaskWaker> {
+        let parked = ParkedThread::default();
+        parked.set(Some(thread));
+        TaskWaker::parking(parked)
+    }
+
+    /// A waker that unparks whichever thread is parked in `thread` at the time.
+    pub(crate) fn parking(thread: ParkedThread) -> Arc<TaskWaker> {
+        Arc::new(TaskWaker {
+            woken: AtomicBool::new(true),
+            thread,
+        })
+    }
+
+    pub fn is_woken(&self) -> bool {
+        self.woken.load(Ordering::SeqCst)
+    }
+
+    /// Clears the flag before a poll, returning whether it was set.
+    pub fn take_woken(&self) -> bool {
+        self.woken.swap(false, Ordering::SeqCst)
+    }
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::SeqCst);
+        self.thread.unpark();
+    }
+}
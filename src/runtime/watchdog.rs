@@ -0,0 +1,76 @@
+use crate::runtime::dump::{TaskDump, TaskDumper, TaskState};
+use crate::runtime::executor::TaskId;
+use std::{
+    collections::HashSet,
+    io,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+/// A thread that watches a [`TaskDumper`] and flags any task that has been inside a single
+/// poll for longer than `threshold`, while that poll is still running. This catches tasks
+/// that block their worker outright, which a slow poll threshold only reports once the
+/// poll finally returns.
+///
+/// Each stuck poll is reported once. The thread stops when the watchdog is dropped.
+pub struct Watchdog {
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Watchdog {
+    /// Starts a watchdog that prints its reports.
+    pub fn spawn(dumper: TaskDumper, threshold: Duration) -> io::Result<Watchdog> {
+        Watchdog::spawn_with(dumper, threshold, |task| {
+            println!("Watchdog: {} is still being polled; is it blocking?", task);
+        })
+    }
+
+    pub fn spawn_with<F>(dumper: TaskDumper, threshold: Duration, report: F) -> io::Result<Watchdog>
+    where
+        F: Fn(&TaskDump) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let interval = (threshold / 4).max(Duration::from_millis(1));
+        let thread = thread::Builder::new()
+            .name(String::from("watchdog"))
+            .spawn(move || {
+                // Polls already reported, by task and poll count.
+                let mut reported = HashSet::<(TaskId, u64)>::new();
+                while !stopped.load(Ordering::SeqCst) {
+                    let mut stuck = HashSet::new();
+                    for task in dumper.dump() {
+                        if task.state == TaskState::Running
+                            && task.since_last_poll.is_some_and(|since| since >= threshold)
+                        {
+                            if !reported.contains(&(task.id, task.polls)) {
+                                report(&task);
+                            }
+                            stuck.insert((task.id, task.polls));
+                        }
+                    }
+                    reported = stuck;
+                    thread::park_timeout(interval);
+                }
+            })?;
+        Ok(Watchdog {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
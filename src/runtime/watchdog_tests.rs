@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use crate::runtime::Builder;
+    use crate::runtime::executor::Executor;
+    use crate::runtime::watchdog::Watchdog;
+    use std::sync::{Arc, Mutex, mpsc};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_slow_polls_are_reported() {
+        let slow = Arc::new(Mutex::new(Vec::new()));
+        let seen = slow.clone();
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1)
+            .slow_poll_threshold(Duration::from_millis(20))
+            .on_slow_poll(move |meta| {
+                seen.lock()
+                    .unwrap()
+                    .push((meta.name.clone(), meta.location.line(), meta.last_poll));
+            })
+            .build()
+            .unwrap();
+        let line = line!() + 1;
+        let blocking = runtime.spawn_named("blocking", async { thread::sleep(Duration::from_millis(30)) });
+        let quick = runtime.spawn_named("quick", async {});
+        blocking.recv_timeout(Duration::from_secs(5)).unwrap();
+        quick.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(runtime.shutdown_timeout(Duration::from_secs(5)));
+
+        let slow = slow.lock().unwrap();
+        assert_eq!(slow.len(), 1);
+        let (name, reported_line, took) = &slow[0];
+        assert_eq!(name.as_deref(), Some("blocking"));
+        assert_eq!(*reported_line, line);
+        assert!(took.unwrap() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_watchdog_flags_a_poll_that_has_not_returned() {
+        let mut executor = Executor::new();
+        let (tx, rx) = mpsc::channel();
+        let _watchdog = Watchdog::spawn_with(
+            executor.dumper(),
+            Duration::from_millis(20),
+            move |task| {
+                let _ = tx.send((task.name.clone(), task.since_last_poll));
+            },
+        )
+        .unwrap();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        executor.spawn_named("stuck", async move {
+            let _ = release_rx.recv();
+        });
+        let worker = thread::spawn(move || executor.run());
+
+        // Reported while the poll is still blocked, and only once for that poll.
+        let (name, since) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(name.as_deref(), Some("stuck"));
+        assert!(since.unwrap() >= Duration::from_millis(20));
+        assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+
+        release_tx.send(()).unwrap();
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn test_runtime_watchdog_needs_a_threshold() {
+        let err = Builder::new_current_thread().watchdog(true).build().err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("slow_poll_threshold"));
+    }
+}
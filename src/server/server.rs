@@ -1,116 +1,317 @@
-use crate::data::data_layer::Data;
-use crate::runtime::{executor::Executor, sleep::Sleep};
+use crate::data::{
+    data_layer::Data,
+    stream::{Decoded, StreamDecoder},
+};
+use crate::runtime::{
+    Builder,
+    fs::OpenOptions,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, poll_would_block},
+    signal::{Signal, SignalKind, signal},
+    sleep::Sleep,
+    spawn_blocking,
+    sys::{self, POLLIN, PollFd},
+    unix::{UnixListener, UnixStream},
+};
 use std::{
-    io::{self, Cursor, ErrorKind, Read, Write},
+    fmt, fs, future,
+    io::{self, ErrorKind, Read, Write},
     net::{TcpListener, TcpStream},
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        mpsc::channel,
-    },
-    thread,
+    os::fd::{AsRawFd, RawFd},
+    os::unix::{fs::FileTypeExt, net},
+    pin::{Pin, pin},
+    sync::mpsc::{self, TryRecvError},
+    task::{Context, Poll},
+    time::{Duration, Instant},
 };
 
-static FLAGS: [AtomicBool; 3] = [
-    AtomicBool::new(false),
-    AtomicBool::new(false),
-    AtomicBool::new(false),
-];
+crate::task_local! {
+    // The peer of the connection a handler task is serving, for its log lines.
+    static PEER: String;
+}
 
-macro_rules! spawn_worker {
-    ($name:expr, $rx:expr, $flag:expr) => {
-        thread::spawn(move || {
-            let mut executor = Executor::new();
-            loop {
-                if let Ok(stream) = $rx.try_recv() {
-                    println!(
-                        "{} Received connection: {}",
-                        $name,
-                        stream.peer_addr().unwrap()
-                    );
-                    executor.spawn(handle_client(stream));
-                } else {
-                    if executor.polling.len() == 0 {
-                        println!("{} is sleeping", $name);
-                        $flag.store(true, Ordering::SeqCst);
-                        thread::park();
-                    }
-                }
-                executor.poll();
+const WORKERS: usize = 3;
+// How long shutdown waits for connected clients to finish.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+impl Listener {
+    async fn accept(&self) -> io::Result<Connection> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, _) =
+                    future::poll_fn(|cx| poll_would_block(cx, || listener.accept())).await?;
+                stream.set_nonblocking(true)?;
+                Ok(Connection::Tcp(stream))
             }
-        })
-    };
+            Listener::Unix(listener) => listener.accept().await.map(|(s, _)| Connection::Unix(s)),
+        }
+    }
+}
+impl AsRawFd for Listener {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Listener::Tcp(listener) => listener.as_raw_fd(),
+            Listener::Unix(listener) => listener.as_raw_fd(),
+        }
+    }
 }
 
-async fn handle_client(mut stream: TcpStream) -> std::io::Result<()> {
-    stream.set_nonblocking(true)?;
-    let mut buffer = Vec::new();
+enum Connection {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+impl fmt::Display for Connection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Connection::Tcp(stream) => match stream.peer_addr() {
+                Ok(addr) => write!(f, "{}", addr),
+                Err(_) => write!(f, "unknown tcp peer"),
+            },
+            // Connecting Unix sockets are usually unbound, so there is no peer path to show.
+            Connection::Unix(_) => write!(f, "unix socket peer"),
+        }
+    }
+}
+impl AsyncRead for Connection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Connection::Tcp(stream) => poll_would_block(cx, || stream.read(buf)),
+            Connection::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+impl AsyncWrite for Connection {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Connection::Tcp(stream) => poll_would_block(cx, || stream.write(buf)),
+            Connection::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Connection::Tcp(stream) => Poll::Ready(stream.flush()),
+            Connection::Unix(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+}
+
+async fn handle_client(mut stream: Connection) -> std::io::Result<()> {
+    // Older clients still send headerless native-endian frames.
+    let mut decoder = StreamDecoder::with_decode_fn(Data::decode_frame_with_legacy);
     let mut local_buf = [0; 1024];
+    // Reused for every message logged on this connection.
+    let mut line = Vec::new();
+    let mut handled = 0;
     loop {
-        match stream.read(&mut local_buf) {
+        match decoder.decode() {
+            Ok(Decoded::Complete(message)) => {
+                PEER.with(|peer| println!("Received message from {}: {:?}", peer, message));
+                if let Err(e) = log_message(&message, &mut line).await {
+                    println!("Failed to persist message: {}", e);
+                }
+                handled += 1;
+                continue;
+            }
+            // Reply once everything the client sent so far has been handled.
+            Ok(Decoded::NeedMore(_)) if handled > 0 && decoder.buffered().is_empty() => break,
+            Ok(Decoded::NeedMore(_)) => {}
+            Err(e) => {
+                PEER.with(|peer| println!("Failed to decode message from {}: {}", peer, e));
+                break;
+            }
+        }
+        match stream.read(&mut local_buf).await {
             Ok(0) => {
+                let partial = decoder.buffered().len();
+                if partial > 0 {
+                    PEER.with(|peer| {
+                        println!(
+                            "Truncated message from {}: connection closed {} bytes into a frame",
+                            peer, partial
+                        )
+                    });
+                }
                 break;
             }
             Ok(len) => {
-                buffer.extend_from_slice(&local_buf[..len]);
-            }
-            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
-                if buffer.len() > 0 {
-                    break;
-                }
-                Sleep::new(std::time::Duration::from_millis(10)).await;
-                continue;
+                decoder.feed(&local_buf[..len]);
             }
             Err(e) => {
-                println!("Failed to read from connection: {}", e);
+                PEER.with(|peer| println!("Failed to read from {}: {}", peer, e));
+                break;
             }
         }
     }
-    match Data::deserialize(&mut Cursor::new(buffer.as_slice())) {
-        Ok(message) => {
-            println!("Received message: {:?}", message);
-        }
-        Err(e) => {
-            println!("Failed to decode message: {}", e);
-        }
-    }
     Sleep::new(std::time::Duration::from_secs(1)).await;
-    stream.write_all(b"Hello, client!")?;
+    stream.write_all(b"Hello, client!").await?;
     Ok(())
 }
 
-fn main() -> io::Result<()> {
-    let listener = TcpListener::bind("127.0.0.1:7878")?;
-    println!("Server listening on port 7878");
+async fn log_message(message: &Data, line: &mut Vec<u8>) -> io::Result<()> {
+    line.clear();
+    writeln!(line, "{:?}", message)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open("messages.log")
+        .await?;
+    file.write_all(line).await?;
+    file.flush().await
+}
 
-    let (one_tx, one_rx) = channel::<TcpStream>();
-    let (two_tx, two_rx) = channel::<TcpStream>();
-    let (three_tx, three_rx) = channel::<TcpStream>();
+enum Event {
+    Signal(SignalKind),
+    Connection(io::Result<Connection>),
+}
 
-    let one = spawn_worker!("One", one_rx, &FLAGS[0]);
-    let two = spawn_worker!("Two", two_rx, &FLAGS[1]);
-    let three = spawn_worker!("Three", three_rx, &FLAGS[2]);
+// Sleeps in poll(2) until `listener` has a connection waiting or `interrupt` is written to.
+fn wait_for_connection(listener: RawFd, interrupt: RawFd) -> io::Result<()> {
+    let mut fds = [PollFd::new(listener, POLLIN), PollFd::new(interrupt, POLLIN)];
+    sys::poll(&mut fds, -1).map(drop)
+}
 
-    let router = [one_tx, two_tx, three_tx];
-    let threads = [one, two, three];
-    let mut index = 0;
+// Waits for whichever comes first, a signal or a new connection. The listener is watched
+// from the blocking pool, so an idle server parks rather than polling it in a loop.
+async fn next_event(
+    listener: &Listener,
+    signals: &mut [Signal],
+    interrupt: &(net::UnixStream, net::UnixStream),
+) -> Event {
+    loop {
+        let fds = (listener.as_raw_fd(), interrupt.1.as_raw_fd());
+        let mut waiting = pin!(spawn_blocking(move || wait_for_connection(fds.0, fds.1)));
+        let woken = future::poll_fn(|cx| {
+            for signal in signals.iter_mut() {
+                if signal.poll_recv(cx).is_ready() {
+                    return Poll::Ready(Err(signal.kind()));
+                }
+            }
+            waiting.as_mut().poll(cx).map(|result| Ok(result.and_then(|ready| ready)))
+        })
+        .await;
+        match woken {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return Event::Connection(Err(e)),
+            Err(kind) => {
+                // Stop the poll before the listener can be closed under it.
+                let _ = (&interrupt.0).write(&[1]);
+                let _ = waiting.await;
+                while matches!((&interrupt.1).read(&mut [0; 16]), Ok(n) if n > 0) {}
+                return Event::Signal(kind);
+            }
+        }
+        // Take the connection without waiting for another if it is gone by now, e.g.
+        // reset by its client.
+        let mut accept = pin!(listener.accept());
+        let accepted = future::poll_fn(|cx| Poll::Ready(accept.as_mut().poll(cx))).await;
+        if let Poll::Ready(accepted) = accepted {
+            return Event::Connection(accepted);
+        }
+    }
+}
+
+// Clears a socket left behind by an earlier run. Anything else at `path` is not ours to
+// delete, so the bind fails as if the address were taken.
+fn remove_stale_socket(path: &str) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(
+            ErrorKind::AddrInUse,
+            format!("{} exists and is not a socket", path),
+        )),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
 
-    for stream in listener.incoming() {
-        match stream {
-            Ok(stream) => {
-                let _ = router[index].send(stream);
-                if FLAGS[index].load(Ordering::SeqCst) {
-                    FLAGS[index].store(false, Ordering::SeqCst);
-                    threads[index].thread().unpark();
+fn main() -> io::Result<()> {
+    // Pass a socket path as the first argument to listen on a Unix socket instead.
+    let socket_path = std::env::args().nth(1);
+    let listener = match socket_path.clone() {
+        Some(path) => {
+            remove_stale_socket(&path)?;
+            let listener = UnixListener::bind(&path)?;
+            println!("Server listening on {}", path);
+            Listener::Unix(listener)
+        }
+        None => {
+            let listener = TcpListener::bind("127.0.0.1:7878")?;
+            listener.set_nonblocking(true)?;
+            println!("Server listening on port 7878");
+            Listener::Tcp(listener)
+        }
+    };
+
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(WORKERS)
+        .thread_name("server-worker")
+        .enable_all()
+        // A handler that blocks on std I/O would stall every connection on its worker.
+        .slow_poll_threshold(Duration::from_millis(100))
+        .watchdog(true)
+        .build()?;
+    // SIGHUP asks for a reload, SIGUSR1 prints the live tasks, and SIGINT and SIGTERM shut
+    // the server down.
+    let mut signals = vec![
+        signal(SignalKind::INTERRUPT)?,
+        signal(SignalKind::TERMINATE)?,
+        signal(SignalKind::HANGUP)?,
+        signal(SignalKind::USER_DEFINED1)?,
+    ];
+    // Written to when a signal arrives while a connection is being waited for.
+    let interrupt = net::UnixStream::pair()?;
+    interrupt.0.set_nonblocking(true)?;
+    interrupt.1.set_nonblocking(true)?;
+    let mut clients = Vec::new();
+    let kind = runtime.block_on(async {
+        loop {
+            match next_event(&listener, &mut signals, &interrupt).await {
+                Event::Signal(SignalKind::HANGUP) => println!("Reload requested"),
+                Event::Signal(SignalKind::USER_DEFINED1) => {
+                    for task in runtime.handle().dump() {
+                        println!("{}", task);
+                    }
                 }
-                index += 1; // cycle through the index of threads
-                if index == 3 {
-                    index = 0;
+                Event::Signal(kind) => return kind,
+                Event::Connection(Ok(stream)) => {
+                    println!("Received connection: {}", stream);
+                    let peer = stream.to_string();
+                    clients.retain(|client: &mpsc::Receiver<_>| {
+                        matches!(client.try_recv(), Err(TryRecvError::Empty))
+                    });
+                    clients.push(runtime.spawn_named(
+                        format!("handle_client {}", peer),
+                        PEER.scope(peer, handle_client(stream)),
+                    ));
+                }
+                Event::Connection(Err(e)) => {
+                    println!("Connection failed: {}", e);
                 }
-            }
-            Err(e) => {
-                println!("Connection failed: {}", e);
             }
         }
+    });
+
+    println!("Received signal {}, shutting down", kind.as_raw());
+    drop(listener);
+    if let Some(path) = &socket_path
+        && let Err(e) = remove_stale_socket(path)
+    {
+        println!("Failed to remove {}: {}", path, e);
+    }
+    // Let the clients already connected get their reply before the workers stop.
+    let deadline = Instant::now() + DRAIN_TIMEOUT;
+    for client in clients {
+        let _ = client.recv_timeout(deadline.saturating_duration_since(Instant::now()));
+    }
+    if !runtime.shutdown_timeout(Duration::from_secs(1)) {
+        println!("Workers did not stop in time");
     }
     Ok(())
 }